@@ -0,0 +1,235 @@
+use crate::{
+    AppWindow, SettingData, UiData,
+    logic::{
+        APP_PATH,
+        anime::{AnimeError, download::fetch_image, record::AnimeRecord},
+        config::get_config,
+        storage::hash_bytes,
+    },
+};
+use image::{ImageFormat, imageops::FilterType};
+use reqwest::Client;
+use slint::{ComponentHandle, Rgba8Pixel, SharedPixelBuffer, Weak};
+use std::{
+    collections::HashSet,
+    fs::File,
+    io::ErrorKind,
+    path::{Path, PathBuf},
+    time::SystemTime,
+};
+
+// 封面以地址的哈希命名，不同季度的同名番剧不会互相覆盖
+// 文件的修改时间作为最后使用时间，超出容量时删除最久没有使用的封面
+fn get_cover_dir() -> PathBuf {
+    APP_PATH.join("covers")
+}
+
+fn find_cached_cover(cover_url: &str) -> Option<PathBuf> {
+    if cover_url.is_empty() {
+        return None;
+    }
+    let hash = hash_bytes(cover_url.as_bytes());
+    // 保存时使用格式的第一个扩展名
+    ImageFormat::all()
+        .filter_map(|format| format.extensions_str().first())
+        .map(|ext| get_cover_dir().join(format!("{}.{}", hash, ext)))
+        .find(|path| path.exists())
+}
+
+// 旧版本按番剧名称保存为<名称>.jpg
+fn get_legacy_cover_path(name: &str) -> PathBuf {
+    get_cover_dir().join(name).with_extension("jpg")
+}
+
+// 按图片内容判断格式，不是图片的内容不会保存
+fn save_cover(cover_url: &str, bytes: &[u8]) -> Result<PathBuf, AnimeError> {
+    let format = image::guess_format(bytes)?;
+    let extension = format.extensions_str().first().copied().unwrap_or("img");
+    let path = get_cover_dir().join(format!(
+        "{}.{}",
+        hash_bytes(cover_url.as_bytes()),
+        extension
+    ));
+    std::fs::write(&path, bytes)?;
+    Ok(path)
+}
+
+pub async fn get_cover(record: AnimeRecord, client: Client) -> Result<(), AnimeError> {
+    if find_cached_cover(&record.cover_url).is_some() {
+        return Ok(());
+    }
+    if record.cover_url.is_empty() {
+        return Err(AnimeError::Parse(format!("{}没有封面地址", record.name)));
+    }
+    let legacy_path = get_legacy_cover_path(&record.name);
+    let bytes = if legacy_path.exists() {
+        // 旧版本下载的封面移动到新的位置
+        let bytes = std::fs::read(&legacy_path)?;
+        std::fs::remove_file(&legacy_path)?;
+        bytes
+    } else if !record.cover_url.starts_with("http://") && !record.cover_url.starts_with("https://")
+    {
+        // 本地数据源可以直接给出封面文件的路径
+        std::fs::read(&record.cover_url)?
+    } else {
+        fetch_image(&client, &record.cover_url).await?
+    };
+    save_cover(&record.cover_url, &bytes)?;
+    Ok(())
+}
+
+// 没有封面地址的旧缓存按名称查找封面
+fn find_cover(record: &AnimeRecord) -> Option<PathBuf> {
+    find_cached_cover(&record.cover_url).or_else(|| {
+        let legacy_path = get_legacy_cover_path(&record.name);
+        legacy_path.exists().then_some(legacy_path)
+    })
+}
+
+// 缩略图按显示时的像素尺寸保存，高分屏使用单独的缩放版本
+fn get_thumbnail_dir() -> PathBuf {
+    get_cover_dir().join("thumbs")
+}
+
+fn get_thumbnail_path(cover_path: &Path, (width, height): (u32, u32)) -> PathBuf {
+    let stem = cover_path.file_stem().unwrap_or_default().to_string_lossy();
+    get_thumbnail_dir().join(format!("{}-{}x{}.png", stem, width, height))
+}
+
+// 封面在界面上的像素尺寸，需要在界面线程调用
+pub fn get_thumbnail_size(app: &AppWindow) -> (u32, u32) {
+    let ui_data = app.global::<UiData>();
+    let scale_factor = app.window().scale_factor();
+    let to_pixels = |length: f32| ((length * scale_factor).round() as u32).max(1);
+    (
+        to_pixels(ui_data.get_cover_width()),
+        to_pixels(ui_data.get_cover_height()),
+    )
+}
+
+// 优先使用已经缩放好的缩略图，没有时从原图缩放并保存
+pub fn load_thumbnail(
+    record: &AnimeRecord,
+    size: (u32, u32),
+) -> Result<SharedPixelBuffer<Rgba8Pixel>, AnimeError> {
+    let path = find_cover(record)
+        .ok_or_else(|| std::io::Error::new(ErrorKind::NotFound, "封面文件不存在"))?;
+    touch_cover(&path);
+    let thumbnail_path = get_thumbnail_path(&path, size);
+    let img = match image::open(&thumbnail_path) {
+        Ok(img) => {
+            touch_cover(&thumbnail_path);
+            img.into_rgba8()
+        }
+        Err(_) => {
+            let img = image::open(&path)?
+                .resize_to_fill(size.0, size.1, FilterType::Triangle)
+                .into_rgba8();
+            // 保存失败时下次重新缩放
+            if let Err(err) = std::fs::create_dir_all(get_thumbnail_dir())
+                .map_err(image::ImageError::from)
+                .and_then(|_| img.save(&thumbnail_path))
+            {
+                eprintln!("{}保存失败：{}", thumbnail_path.display(), err);
+            }
+            img
+        }
+    };
+    let buffer =
+        SharedPixelBuffer::<Rgba8Pixel>::clone_from_slice(img.as_raw(), img.width(), img.height());
+    Ok(buffer)
+}
+
+// 更新最后使用时间，失败不影响加载
+fn touch_cover(path: &Path) {
+    if let Err(err) = File::options()
+        .write(true)
+        .open(path)
+        .and_then(|file| file.set_modified(SystemTime::now()))
+    {
+        eprintln!("{}更新使用时间失败：{}", path.display(), err);
+    }
+}
+
+// (路径, 大小, 最后使用时间)，包括缩略图，按最后使用时间从旧到新排列
+fn get_cover_files() -> Vec<(PathBuf, u64, SystemTime)> {
+    let mut files = [get_cover_dir(), get_thumbnail_dir()]
+        .iter()
+        .filter_map(|dir| std::fs::read_dir(dir).ok())
+        .flatten()
+        .filter_map(|e| e.ok())
+        .filter_map(|e| {
+            let metadata = e.metadata().ok()?;
+            if !metadata.is_file() {
+                return None;
+            }
+            let modified = metadata.modified().unwrap_or(SystemTime::UNIX_EPOCH);
+            Some((e.path(), metadata.len(), modified))
+        })
+        .collect::<Vec<(PathBuf, u64, SystemTime)>>();
+    files.sort_by_key(|(_, _, modified)| *modified);
+    files
+}
+
+pub fn get_cover_cache_size() -> u64 {
+    get_cover_files().iter().map(|(_, size, _)| size).sum()
+}
+
+// 超出容量时从最久没有使用的封面开始删除，正在显示的季度的封面和缩略图不删除
+pub fn enforce_cover_quota(days: &[Vec<AnimeRecord>]) {
+    let limit = get_config().cover_cache_limit_mb.max(0) as u64 * 1024 * 1024;
+    let kept_stems = days
+        .iter()
+        .flatten()
+        .filter_map(find_cover)
+        .filter_map(|path| Some(path.file_stem()?.to_string_lossy().to_string()))
+        .collect::<HashSet<String>>();
+    let files = get_cover_files();
+    let mut total = files.iter().map(|(_, size, _)| size).sum::<u64>();
+    for (path, size, _) in files {
+        if total <= limit {
+            break;
+        }
+        if kept_stems.contains(&get_cover_stem(&path)) {
+            continue;
+        }
+        match std::fs::remove_file(&path) {
+            Ok(()) => total -= size,
+            Err(err) => eprintln!("{}删除失败：{}", path.display(), err),
+        }
+    }
+}
+
+// 缩略图的文件名为<封面文件名>-<宽>x<高>.png，返回对应封面的文件名
+fn get_cover_stem(path: &Path) -> String {
+    let stem = path.file_stem().unwrap_or_default().to_string_lossy();
+    if path.parent() == Some(get_thumbnail_dir().as_path()) {
+        stem.rsplit_once('-')
+            .map_or(stem.to_string(), |(stem, _)| stem.to_string())
+    } else {
+        stem.to_string()
+    }
+}
+
+fn clear_cover_cache() {
+    for (path, _, _) in get_cover_files() {
+        if let Err(err) = std::fs::remove_file(&path) {
+            eprintln!("{}删除失败：{}", path.display(), err);
+        }
+    }
+}
+
+pub fn set_cover_logic(app: Weak<AppWindow>) {
+    let app = app.unwrap();
+    let weak = app.as_weak();
+    app.global::<SettingData>().on_clear_cover_cache(move || {
+        clear_cover_cache();
+        refresh_cover_cache_size(&weak.unwrap());
+    });
+}
+
+pub fn refresh_cover_cache_size(app: &AppWindow) {
+    let size = get_cover_cache_size() as f64 / 1024.0 / 1024.0;
+    app.global::<SettingData>()
+        .set_cover_cache_size(format!("{:.1} MB", size).into());
+}
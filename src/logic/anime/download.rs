@@ -0,0 +1,93 @@
+use crate::logic::{
+    anime::{AnimeError, cover::get_cover, record::AnimeRecord},
+    network::{RUNTIME, get_client},
+};
+use reqwest::{Client, StatusCode, header::CONTENT_TYPE};
+use std::{
+    sync::{Arc, LazyLock},
+    time::Duration,
+};
+use tokio::sync::Semaphore;
+
+// 同时下载的封面数量
+const MAX_CONCURRENT_DOWNLOADS: usize = 4;
+// 失败后最多重试的次数，第n次重试前等待RETRY_BASE_DELAY * 2^n
+const MAX_RETRIES: u32 = 3;
+const RETRY_BASE_DELAY: Duration = Duration::from_millis(500);
+// 所有下载共用，同时刷新多个季度时总的并发数也不超过MAX_CONCURRENT_DOWNLOADS
+static DOWNLOAD_SEMAPHORE: LazyLock<Arc<Semaphore>> =
+    LazyLock::new(|| Arc::new(Semaphore::new(MAX_CONCURRENT_DOWNLOADS)));
+
+// 下载缺少的封面，已缓存的封面直接跳过
+pub fn download_covers(days: &[Vec<AnimeRecord>]) {
+    let client = match get_client() {
+        Ok(client) => client,
+        Err(err) => {
+            eprintln!("封面下载失败：{}", err);
+            return;
+        }
+    };
+    RUNTIME.block_on(async {
+        let mut handles = vec![];
+        for record in days.iter().flatten() {
+            let semaphore = DOWNLOAD_SEMAPHORE.clone();
+            let record = record.clone();
+            let client = client.clone();
+            handles.push(tokio::spawn(async move {
+                let _permit = semaphore.acquire_owned().await;
+                get_cover(record, client).await
+            }));
+        }
+        // 个别封面下载失败不影响整体结果
+        for handle in handles {
+            match handle.await {
+                Ok(Err(err)) => eprintln!("封面下载失败：{}", err),
+                Err(err) => eprintln!("封面下载任务异常：{}", err),
+                Ok(Ok(())) => {}
+            }
+        }
+    });
+}
+
+// 下载一张图片，网络错误、超时和服务器错误时按指数退避重试
+pub async fn fetch_image(client: &Client, url: &str) -> Result<Vec<u8>, AnimeError> {
+    let mut retries = 0;
+    loop {
+        match try_fetch_image(client, url).await {
+            Err(AnimeError::Network(err)) if retries < MAX_RETRIES && is_retryable(&err) => {
+                tokio::time::sleep(RETRY_BASE_DELAY * 2u32.pow(retries)).await;
+                retries += 1;
+            }
+            result => return result,
+        }
+    }
+}
+
+// 只接受成功的响应，内容类型不是图片时不保存（比如错误页面）
+async fn try_fetch_image(client: &Client, url: &str) -> Result<Vec<u8>, AnimeError> {
+    let response = client.get(url).send().await?.error_for_status()?;
+    let content_type = response
+        .headers()
+        .get(CONTENT_TYPE)
+        .and_then(|value| value.to_str().ok())
+        .unwrap_or_default()
+        .to_ascii_lowercase();
+    // 部分图床不返回内容类型或者返回octet-stream，保存时再按内容判断格式
+    if !content_type.is_empty()
+        && !content_type.starts_with("image/")
+        && !content_type.starts_with("application/octet-stream")
+    {
+        return Err(AnimeError::Parse(format!(
+            "{}返回的不是图片：{}",
+            url, content_type
+        )));
+    }
+    Ok(response.bytes().await?.to_vec())
+}
+
+fn is_retryable(err: &reqwest::Error) -> bool {
+    match err.status() {
+        Some(status) => status.is_server_error() || status == StatusCode::TOO_MANY_REQUESTS,
+        None => err.is_timeout() || err.is_connect() || err.is_request() || err.is_body(),
+    }
+}
@@ -0,0 +1,52 @@
+use crate::logic::{
+    APP_PATH,
+    anime::{
+        AnimeError,
+        record::AnimeCache,
+        source::{ScheduleSource, SeasonPage},
+        yuc::parse_schedule,
+    },
+    config::Config,
+};
+use std::path::PathBuf;
+
+// 本地文件数据源，依次查找<目录>/<季度>.json和<目录>/<季度>.html
+// json与番剧缓存格式相同，html为保存下来的长门有C页面
+pub struct LocalSource {
+    dir: PathBuf,
+}
+
+impl LocalSource {
+    // 没有配置目录时使用数据目录下的anime_source
+    pub fn new(config: &Config) -> Self {
+        let dir = if config.anime_local_dir.trim().is_empty() {
+            APP_PATH.join("anime_source")
+        } else {
+            PathBuf::from(config.anime_local_dir.trim())
+        };
+        LocalSource { dir }
+    }
+}
+
+impl ScheduleSource for LocalSource {
+    fn name(&self) -> &'static str {
+        "本地文件"
+    }
+
+    fn fetch_season(&self, suffix: &str) -> Result<SeasonPage, AnimeError> {
+        let json_path = self.dir.join(format!("{}.json", suffix));
+        if json_path.exists() {
+            let text = std::fs::read_to_string(json_path)?;
+            let cache: AnimeCache = serde_json::from_str(&text)?;
+            return Ok(SeasonPage {
+                page: text,
+                days: cache.days,
+            });
+        }
+        let html = std::fs::read_to_string(self.dir.join(format!("{}.html", suffix)))?;
+        Ok(SeasonPage {
+            days: parse_schedule(&html),
+            page: html,
+        })
+    }
+}
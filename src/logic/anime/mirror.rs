@@ -0,0 +1,36 @@
+use crate::logic::anime::{
+    AnimeError,
+    record::AnimeCache,
+    source::{ScheduleSource, SeasonPage, get_text},
+};
+
+// 提供JSON数据的镜像，地址为<base_url><季度>.json，格式与番剧缓存相同
+pub struct JsonMirrorSource {
+    base_url: String,
+}
+
+impl JsonMirrorSource {
+    pub fn new(base_url: &str) -> Self {
+        JsonMirrorSource {
+            base_url: base_url.to_string(),
+        }
+    }
+}
+
+impl ScheduleSource for JsonMirrorSource {
+    fn name(&self) -> &'static str {
+        "JSON镜像"
+    }
+
+    fn fetch_season(&self, suffix: &str) -> Result<SeasonPage, AnimeError> {
+        if self.base_url.is_empty() {
+            return Err(AnimeError::Unavailable("没有配置镜像地址".to_string()));
+        }
+        let text = get_text(&format!("{}{}.json", self.base_url, suffix))?;
+        let cache: AnimeCache = serde_json::from_str(&text)?;
+        Ok(SeasonPage {
+            page: text,
+            days: cache.days,
+        })
+    }
+}
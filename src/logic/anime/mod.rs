@@ -0,0 +1,398 @@
+use crate::{
+    Anime, AnimeData, AppWindow, DayAnime,
+    logic::{
+        APP_PATH, SlintDate,
+        anime::{
+            cover::{
+                enforce_cover_quota, get_thumbnail_size, load_thumbnail, refresh_cover_cache_size,
+                set_cover_logic,
+            },
+            download::download_covers,
+            local::LocalSource,
+            record::{AnimeCache, AnimeRecord},
+            source::{ScheduleSource, SeasonPage, get_source},
+            watchlist::{WATCHLIST, set_watchlist_logic, sync_followed_todos},
+        },
+        config::{AnimeSourceKind, get_config},
+        network::NetworkError,
+        storage::{load_json, save_json},
+    },
+};
+use chrono::{Datelike, Months, NaiveDate};
+use slint::{
+    ComponentHandle, Image, Model, Rgba8Pixel, SharedPixelBuffer, Weak, invoke_from_event_loop,
+};
+use std::{
+    rc::Rc,
+    sync::atomic::{AtomicU64, Ordering},
+};
+
+mod cover;
+mod download;
+mod local;
+mod mirror;
+mod record;
+mod source;
+#[cfg(test)]
+mod tests;
+mod watchlist;
+mod yuc;
+
+// 每次获取番剧信息时加一，快速切换季度时只有最后一次请求的结果会显示
+static ANIME_GENERATION: AtomicU64 = AtomicU64::new(0);
+
+pub fn set_anime_logic(app_weak: Weak<AppWindow>) {
+    let app = app_weak.unwrap();
+    let anime_data = app.global::<AnimeData>();
+    let weak = app_weak.clone();
+    anime_data.on_update_anime_data(move || {
+        let app = weak.unwrap();
+        let anime_schedule = app.global::<AnimeData>().get_anime_schedule();
+        get_anime(weak.clone(), anime_schedule, false);
+    });
+    let weak = app_weak.clone();
+    anime_data.on_refresh_anime_data(move || {
+        let app = weak.unwrap();
+        let anime_schedule = app.global::<AnimeData>().get_anime_schedule();
+        get_anime(weak.clone(), anime_schedule, true);
+    });
+    set_watchlist_logic(app_weak.clone());
+    set_cover_logic(app_weak);
+}
+
+// 加载一个季度的结果，封面在显示名称之后再逐个加载
+struct AnimeSeason {
+    days: Vec<Vec<AnimeRecord>>,
+    fetched_at: String,
+    // 重新获取后与旧缓存的差异，或者刷新失败的原因
+    message: String,
+}
+
+// 番剧数据获取过程中可能出现的错误
+#[derive(Debug)]
+pub enum AnimeError {
+    Network(reqwest::Error),
+    Io(std::io::Error),
+    Json(serde_json::Error),
+    Image(image::ImageError),
+    // 页面结构和预期不一致，无法解析出番剧列表
+    Parse(String),
+    // 数据源缺少必要的配置
+    Unavailable(String),
+}
+
+impl std::fmt::Display for AnimeError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            AnimeError::Network(err) => write!(f, "网络请求失败：{}", err),
+            AnimeError::Io(err) => write!(f, "文件读写失败：{}", err),
+            AnimeError::Json(err) => write!(f, "数据解析失败：{}", err),
+            AnimeError::Image(err) => write!(f, "图片加载失败：{}", err),
+            AnimeError::Parse(msg) => write!(f, "页面解析失败：{}", msg),
+            AnimeError::Unavailable(msg) => write!(f, "数据源不可用：{}", msg),
+        }
+    }
+}
+
+impl std::error::Error for AnimeError {}
+
+impl From<reqwest::Error> for AnimeError {
+    fn from(err: reqwest::Error) -> Self {
+        AnimeError::Network(err)
+    }
+}
+
+impl From<NetworkError> for AnimeError {
+    fn from(err: NetworkError) -> Self {
+        match err {
+            NetworkError::Io(err) => AnimeError::Io(err),
+            NetworkError::Request(err) => AnimeError::Network(err),
+        }
+    }
+}
+
+impl From<std::io::Error> for AnimeError {
+    fn from(err: std::io::Error) -> Self {
+        AnimeError::Io(err)
+    }
+}
+
+impl From<serde_json::Error> for AnimeError {
+    fn from(err: serde_json::Error) -> Self {
+        AnimeError::Json(err)
+    }
+}
+
+impl From<image::ImageError> for AnimeError {
+    fn from(err: image::ImageError) -> Self {
+        AnimeError::Image(err)
+    }
+}
+
+// force为true时忽略缓存重新获取
+pub fn get_anime(app_weak: Weak<AppWindow>, anime_schedule: SlintDate, force: bool) {
+    let suffix = get_suffix(anime_schedule);
+    let generation = ANIME_GENERATION.fetch_add(1, Ordering::SeqCst) + 1;
+    let app = app_weak.unwrap();
+    let anime_data = app.global::<AnimeData>();
+    anime_data.set_is_loading(true);
+    // 数据源可以在设置中修改，每次加载时更新
+    anime_data.set_source_name(get_source(&get_config()).name().into());
+    let thumbnail_size = get_thumbnail_size(&app);
+    std::thread::spawn(move || {
+        let result = load_anime_list(suffix, force);
+        let days = match &result {
+            Ok(season) => season.days.clone(),
+            Err(_) => vec![],
+        };
+        let weak = app_weak.clone();
+        invoke_from_event_loop(move || {
+            // 已经切换到其他季度，丢弃过期的结果
+            if ANIME_GENERATION.load(Ordering::SeqCst) != generation {
+                return;
+            }
+            let app = weak.unwrap();
+            let anime_data = app.global::<AnimeData>();
+            let mut week_anime_list = anime_data
+                .get_week_anime_list()
+                .iter()
+                .collect::<Vec<DayAnime>>();
+            match result {
+                Ok(season) => {
+                    for (day_anime, records) in week_anime_list.iter_mut().zip(season.days) {
+                        let list = records
+                            .into_iter()
+                            .map(|record| {
+                                let mut anime = record.to_anime();
+                                WATCHLIST.with(|watchlist| {
+                                    watchlist.borrow().apply_to_anime(&mut anime)
+                                });
+                                anime
+                            })
+                            .collect::<Vec<Anime>>();
+                        day_anime.anime_list = Rc::new(slint::VecModel::from(list)).into();
+                    }
+                    anime_data.set_error_message("".into());
+                    anime_data.set_fetched_at(season.fetched_at.into());
+                    anime_data.set_refresh_message(season.message.into());
+                }
+                Err(err) => {
+                    anime_data.set_fetched_at("".into());
+                    anime_data.set_refresh_message("".into());
+                    for day_anime in week_anime_list.iter_mut() {
+                        day_anime.anime_list = Default::default();
+                    }
+                    anime_data.set_error_message(format!("番剧信息获取失败，{}", err).into());
+                }
+            }
+            anime_data.set_week_anime_list(Rc::new(slint::VecModel::from(week_anime_list)).into());
+            anime_data.set_is_loading(false);
+            sync_followed_todos(&app);
+        })
+        .unwrap();
+        if is_empty_season(&days) {
+            return;
+        }
+        download_covers(&days);
+        load_covers(app_weak, generation, days, thumbnail_size);
+    });
+}
+
+// 在后台线程逐个解码缩略图，解码完成一个就更新一个，切换季度后停止
+// 全部加载完成后再清理封面缓存，避免刚下载的封面还没显示就被删除
+fn load_covers(
+    app_weak: Weak<AppWindow>,
+    generation: u64,
+    days: Vec<Vec<AnimeRecord>>,
+    thumbnail_size: (u32, u32),
+) {
+    for (day, records) in days.iter().enumerate() {
+        for (row, record) in records.iter().enumerate() {
+            if ANIME_GENERATION.load(Ordering::SeqCst) != generation {
+                return;
+            }
+            let img = match load_thumbnail(record, thumbnail_size) {
+                Ok(img) => img,
+                Err(err) => {
+                    eprintln!("{}的封面{}", record.name, err);
+                    continue;
+                }
+            };
+            let weak = app_weak.clone();
+            let id = record.get_id();
+            let result = invoke_from_event_loop(move || {
+                if ANIME_GENERATION.load(Ordering::SeqCst) != generation {
+                    return;
+                }
+                set_anime_cover(&weak.unwrap(), day, row, &id, img);
+            });
+            if result.is_err() {
+                return;
+            }
+        }
+    }
+    enforce_cover_quota(&days);
+    let _ = invoke_from_event_loop(move || refresh_cover_cache_size(&app_weak.unwrap()));
+}
+
+fn set_anime_cover(
+    app: &AppWindow,
+    day: usize,
+    row: usize,
+    id: &str,
+    img: SharedPixelBuffer<Rgba8Pixel>,
+) {
+    let week_anime_list = app.global::<AnimeData>().get_week_anime_list();
+    let Some(day_anime) = week_anime_list.row_data(day) else {
+        return;
+    };
+    // 确认是同一部番剧，避免列表已经变化时设置错封面
+    if let Some(mut anime) = day_anime.anime_list.row_data(row)
+        && anime.id == id
+    {
+        anime.cover = Image::from_rgba8(img);
+        day_anime.anime_list.set_row_data(row, anime);
+    }
+}
+
+fn load_anime_list(suffix: String, force: bool) -> Result<AnimeSeason, AnimeError> {
+    let anime_data_path = APP_PATH.join("data").join(format!("{}.json", suffix));
+    // 缓存损坏时load_json返回None，重新获取；旧版本的缓存在加载时迁移
+    let old_cache = load_json::<AnimeCache>(&anime_data_path);
+    if let Some(cache) = &old_cache
+        && !force
+        && !is_cache_expired(cache, &suffix)
+    {
+        return Ok(load_season(cache, String::new()));
+    }
+    let season = match fetch_season(&suffix) {
+        Ok(season) => season,
+        // 刷新失败时继续使用旧缓存
+        Err(err) => match old_cache {
+            Some(cache) => {
+                eprintln!("番剧信息刷新失败：{}", err);
+                return Ok(load_season(&cache, format!("刷新失败，{}", err)));
+            }
+            None => return Err(err),
+        },
+    };
+    let cache = AnimeCache::new(season.days, &season.page);
+    if let Err(err) = save_json(&anime_data_path, &cache) {
+        eprintln!("{}保存失败：{}", anime_data_path.display(), err);
+    }
+    let message = match &old_cache {
+        Some(old_cache) => get_diff_message(&cache, old_cache),
+        None => String::new(),
+    };
+    Ok(load_season(&cache, message))
+}
+
+// 过去的季度不会再变化，当前和之后的季度超过刷新间隔后重新获取
+fn is_cache_expired(cache: &AnimeCache, suffix: &str) -> bool {
+    if suffix < get_current_suffix().as_str() {
+        return false;
+    }
+    let ttl = chrono::Duration::hours(get_config().anime_cache_ttl_hours as i64);
+    match cache.get_fetched_at() {
+        Some(fetched_at) => chrono::Local::now().naive_local() - fetched_at > ttl,
+        None => true,
+    }
+}
+
+fn get_diff_message(cache: &AnimeCache, old_cache: &AnimeCache) -> String {
+    if cache.source_hash == old_cache.source_hash {
+        return "番剧表没有变化".to_string();
+    }
+    match cache.diff(old_cache) {
+        (0, 0) => "番剧信息有更新".to_string(),
+        (added, removed) => format!("番剧表已更新，新增{}部，移除{}部", added, removed),
+    }
+}
+
+// 先使用配置的数据源，失败时尝试本地文件，避免数据源改版或无法访问时没有数据
+fn fetch_season(suffix: &str) -> Result<SeasonPage, AnimeError> {
+    let config = get_config();
+    let source = get_source(&config);
+    let err = match source.fetch_season(suffix) {
+        Ok(season) if !is_empty_season(&season.days) => return Ok(season),
+        Ok(_) => AnimeError::Parse("没有找到番剧列表".to_string()),
+        Err(err) => err,
+    };
+    if config.anime_source == AnimeSourceKind::Local {
+        return Err(err);
+    }
+    eprintln!("{}获取失败：{}，尝试本地文件", source.name(), err);
+    match LocalSource::new(&config).fetch_season(suffix) {
+        Ok(season) if !is_empty_season(&season.days) => Ok(season),
+        _ => Err(err),
+    }
+}
+
+fn is_empty_season(week_anime_list: &[Vec<AnimeRecord>]) -> bool {
+    week_anime_list.iter().all(|records| records.is_empty())
+}
+
+fn load_season(cache: &AnimeCache, message: String) -> AnimeSeason {
+    AnimeSeason {
+        days: cache.days.clone(),
+        fetched_at: cache.fetched_at.clone(),
+        message,
+    }
+}
+
+//确保字符串符合文件名的要求，如果不符合要求，则加以修改
+pub fn get_valid_filename(name: &str) -> String {
+    let invalid_chars = ['/', '\\', ':', '*', '?', '"', '<', '>', '|'];
+    let mut valid_name = String::new();
+    for c in name.chars() {
+        if invalid_chars.contains(&c) {
+            valid_name.push('_');
+        } else {
+            valid_name.push(c);
+        }
+    }
+    valid_name
+}
+
+fn get_suffix(date: SlintDate) -> String {
+    match date.month {
+        10 => format!("{}{}", date.year, date.month),
+        _ => format!("{}0{}", date.year, date.month),
+    }
+}
+
+// 当前季度，如"202504"
+fn get_current_suffix() -> String {
+    let date = chrono::Local::now().date_naive();
+    get_suffix(SlintDate {
+        year: date.year(),
+        month: (date.month0() / 3 * 3 + 1) as i32,
+        day: 1,
+    })
+}
+
+// 季度的第一天和最后一天
+fn get_season_range(date: &SlintDate) -> Option<(NaiveDate, NaiveDate)> {
+    let start = NaiveDate::from_ymd_opt(date.year, date.month as u32, 1)?;
+    let end = start.checked_add_months(Months::new(3))?.pred_opt()?;
+    Some((start, end))
+}
+
+pub fn init_anime_schedule(app: Weak<AppWindow>) -> SlintDate {
+    let app = app.unwrap();
+    let date = chrono::Local::now().date_naive();
+    let mut anime_schedule = app.global::<AnimeData>().get_anime_schedule();
+    anime_schedule.year = date.year();
+    anime_schedule.month = match date.month() {
+        1..=3 => 1,
+        4..=6 => 4,
+        7..=9 => 7,
+        10..=12 => 10,
+        _ => {
+            panic!("Invalid month")
+        } //TODO: 错误处理
+    };
+    app.global::<AnimeData>()
+        .set_anime_schedule(anime_schedule.clone());
+    anime_schedule
+}
@@ -0,0 +1,172 @@
+use crate::{
+    Anime,
+    logic::storage::{Migration, hash_bytes, load_versioned},
+};
+use chrono::{Local, NaiveDateTime};
+use serde::{Deserialize, Serialize};
+use serde_json::{Value, json};
+use std::collections::HashSet;
+
+// 番剧缓存的当前版本，修改AnimeRecord的结构时需要加一并在MIGRATIONS末尾追加迁移函数
+pub const ANIME_CACHE_VERSION: u32 = 2;
+// MIGRATIONS[i]把第i版的数据迁移到第i+1版
+const MIGRATIONS: [Migration; ANIME_CACHE_VERSION as usize] = [migrate_v0_to_v1, migrate_v1_to_v2];
+// fetched_at的格式
+pub const FETCHED_AT_FORMAT: &str = "%Y-%m-%d %H:%M:%S";
+
+// 保存到data/<季度>.json的缓存，days按周一到周日排列
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(try_from = "Value")]
+pub struct AnimeCache {
+    version: u32,
+    // 获取数据的时间，为空表示未知，按过期处理
+    pub fetched_at: String,
+    // 数据源原始页面的哈希，用来判断刷新后是否有变化
+    pub source_hash: String,
+    pub days: Vec<Vec<AnimeRecord>>,
+}
+
+// 一部番剧的信息，name同时是封面的文件名，其余字段在数据源中没有时为空
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct AnimeRecord {
+    pub name: String,
+    pub title: String,
+    pub original_title: String,
+    pub broadcast_time: String,
+    pub episodes: String,
+    pub studio: String,
+    pub platforms: Vec<String>,
+    pub tags: Vec<String>,
+    pub cover_url: String,
+}
+
+impl AnimeCache {
+    // 新获取的数据，记录当前时间和page的哈希，page为数据源返回的原始内容
+    pub fn new(days: Vec<Vec<AnimeRecord>>, page: &str) -> Self {
+        AnimeCache {
+            version: ANIME_CACHE_VERSION,
+            fetched_at: Local::now().format(FETCHED_AT_FORMAT).to_string(),
+            source_hash: hash_bytes(page.as_bytes()),
+            days,
+        }
+    }
+
+    pub fn get_fetched_at(&self) -> Option<NaiveDateTime> {
+        NaiveDateTime::parse_from_str(&self.fetched_at, FETCHED_AT_FORMAT).ok()
+    }
+
+    // 与旧数据比较，返回新增和移除的番剧数量
+    pub fn diff(&self, old: &AnimeCache) -> (usize, usize) {
+        let names = |cache: &AnimeCache| {
+            cache
+                .days
+                .iter()
+                .flatten()
+                .map(|r| r.name.clone())
+                .collect::<HashSet<String>>()
+        };
+        let (new_names, old_names) = (names(self), names(old));
+        (
+            new_names.difference(&old_names).count(),
+            old_names.difference(&new_names).count(),
+        )
+    }
+}
+
+impl TryFrom<Value> for AnimeCache {
+    type Error = String;
+
+    // 第0版只保存了每天的番剧名称，没有版本号
+    fn try_from(value: Value) -> Result<Self, Self::Error> {
+        let mut value = load_versioned(value, &MIGRATIONS, "番剧缓存")?;
+        let get_string = |key: &str| {
+            value
+                .get(key)
+                .and_then(Value::as_str)
+                .unwrap_or_default()
+                .to_string()
+        };
+        let fetched_at = get_string("fetched_at");
+        let source_hash = get_string("source_hash");
+        let days = value.get_mut("days").map(Value::take).unwrap_or_default();
+        let days = serde_json::from_value(days).map_err(|e| e.to_string())?;
+        Ok(AnimeCache {
+            version: ANIME_CACHE_VERSION,
+            fetched_at,
+            source_hash,
+            days,
+        })
+    }
+}
+
+// 第0版是Vec<Vec<String>>，名称同时作为标题
+fn migrate_v0_to_v1(value: Value) -> Result<Value, String> {
+    let days: Vec<Vec<String>> = serde_json::from_value(value).map_err(|e| e.to_string())?;
+    let days = days
+        .into_iter()
+        .map(|names| {
+            names
+                .into_iter()
+                .map(|name| json!({ "name": name, "title": name }))
+                .collect::<Vec<Value>>()
+        })
+        .collect::<Vec<Vec<Value>>>();
+    Ok(json!({ "version": 1, "days": days }))
+}
+
+// 第1版没有获取时间和哈希，留空后当前季度会在下次加载时刷新
+fn migrate_v1_to_v2(mut value: Value) -> Result<Value, String> {
+    let map = value.as_object_mut().ok_or("番剧缓存格式错误")?;
+    map.insert("version".to_string(), json!(2));
+    map.insert("fetched_at".to_string(), json!(""));
+    map.insert("source_hash".to_string(), json!(""));
+    Ok(value)
+}
+
+impl AnimeRecord {
+    // 追番列表使用的id，由标题计算，同一部番剧在不同季度和不同数据源中保持一致
+    pub fn get_id(&self) -> String {
+        let title = if self.title.is_empty() {
+            &self.name
+        } else {
+            &self.title
+        };
+        get_title_id(title)
+    }
+
+    // 封面在缩略图加载完成后再设置，之前界面上显示占位图
+    pub fn to_anime(&self) -> Anime {
+        Anime {
+            id: self.get_id().into(),
+            name: self.name.clone().into(),
+            title: self.title.clone().into(),
+            original_title: self.original_title.clone().into(),
+            broadcast_time: self.broadcast_time.clone().into(),
+            episodes: self.episodes.clone().into(),
+            studio: self.studio.clone().into(),
+            platforms: self.platforms.join(" / ").into(),
+            tags: self.tags.join(" ").into(),
+            ..Default::default()
+        }
+    }
+}
+
+// 不同页面中的标题可能有全角半角、大小写、空格和标点的差别，去掉这些差别后再计算哈希
+// 全部是标点的标题规范化后为空，这时使用原标题
+pub fn get_title_id(title: &str) -> String {
+    let normalized = title
+        .chars()
+        .map(|c| match c {
+            '\u{ff01}'..='\u{ff5e}' => char::from_u32(c as u32 - 0xfee0).unwrap_or(c),
+            _ => c,
+        })
+        .filter(|c| c.is_alphanumeric())
+        .flat_map(char::to_lowercase)
+        .collect::<String>();
+    if normalized.is_empty() {
+        hash_bytes(title.as_bytes())
+    } else {
+        hash_bytes(normalized.as_bytes())
+    }
+}
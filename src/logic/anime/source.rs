@@ -0,0 +1,34 @@
+use crate::logic::{
+    anime::{
+        AnimeError, local::LocalSource, mirror::JsonMirrorSource, record::AnimeRecord,
+        yuc::YucWikiSource,
+    },
+    config::{AnimeSourceKind, Config},
+    network,
+};
+
+// 数据源返回的一个季度，page为原始的页面内容，用来判断刷新后是否有变化
+pub struct SeasonPage {
+    pub page: String,
+    pub days: Vec<Vec<AnimeRecord>>,
+}
+
+// 番剧数据源，新增数据源时实现该trait并在get_source中注册
+pub trait ScheduleSource {
+    // 显示在日志中的名称
+    fn name(&self) -> &'static str;
+    // 获取某个季度周一到周日的番剧信息，suffix形如"202504"
+    fn fetch_season(&self, suffix: &str) -> Result<SeasonPage, AnimeError>;
+}
+
+pub fn get_source(config: &Config) -> Box<dyn ScheduleSource> {
+    match config.anime_source {
+        AnimeSourceKind::YucWiki => Box::new(YucWikiSource::new(&config.anime_base_url)),
+        AnimeSourceKind::Local => Box::new(LocalSource::new(config)),
+        AnimeSourceKind::JsonMirror => Box::new(JsonMirrorSource::new(&config.anime_mirror_url)),
+    }
+}
+
+pub fn get_text(url: &str) -> Result<String, AnimeError> {
+    Ok(network::get_text(url)?)
+}
@@ -0,0 +1,197 @@
+use crate::logic::{
+    SlintDate,
+    anime::{
+        get_suffix, get_valid_filename,
+        record::{AnimeRecord, get_title_id},
+        source::ScheduleSource,
+        yuc::{YucWikiSource, parse_schedule},
+    },
+    config::DEFAULT_BASE_URL,
+};
+
+// 手写的页面片段，按长门有C季度页面的结构和class名编写，并非真实页面的抓取
+// 番剧信息是示例数据，页面改版时需要对照真实页面更新
+// 真实页面的解析由parse_live_seasons检查，需要联网，用cargo test -- --ignored运行
+const FIXTURE_202401: &str = include_str!(concat!(
+    env!("CARGO_MANIFEST_DIR"),
+    "/tests/fixtures/yuc/202401.html"
+));
+const FIXTURE_202404: &str = include_str!(concat!(
+    env!("CARGO_MANIFEST_DIR"),
+    "/tests/fixtures/yuc/202404.html"
+));
+const FIXTURE_202410: &str = include_str!(concat!(
+    env!("CARGO_MANIFEST_DIR"),
+    "/tests/fixtures/yuc/202410.html"
+));
+
+fn get_titles(week_anime_list: &[Vec<AnimeRecord>]) -> Vec<Vec<&str>> {
+    week_anime_list
+        .iter()
+        .map(|records| records.iter().map(|r| r.title.as_str()).collect())
+        .collect()
+}
+
+fn find<'a>(week_anime_list: &'a [Vec<AnimeRecord>], title: &str) -> &'a AnimeRecord {
+    week_anime_list
+        .iter()
+        .flatten()
+        .find(|r| r.title == title)
+        .unwrap()
+}
+
+#[test]
+fn parse_202401_weekday_grouping() {
+    let week_anime_list = parse_schedule(FIXTURE_202401);
+    assert_eq!(
+        get_titles(&week_anime_list),
+        vec![
+            vec!["葬送的芙莉莲", "Fate/strange Fake"],
+            vec!["药屋少女的呢喃"],
+            vec![],
+            vec!["我独自升级", "迷宫饭", "异修罗"],
+            vec!["治愈魔法的错误使用方法"],
+            vec!["间谍过家家"],
+            vec!["憧憬成为魔法少女"],
+        ]
+    );
+}
+
+#[test]
+fn parse_202404_weekday_grouping() {
+    let week_anime_list = parse_schedule(FIXTURE_202404);
+    assert_eq!(
+        get_titles(&week_anime_list),
+        vec![
+            vec!["怪兽8号"],
+            vec!["无职转生 第二季 第2部分", "夜晚的水母不会游泳"],
+            vec!["小市民系列"],
+            vec!["鬼灭之刃 柱训练篇"],
+            vec![],
+            vec!["迷宫饭"],
+            vec!["吹响吧！上低音号3"],
+        ]
+    );
+}
+
+#[test]
+fn parse_202410_weekday_grouping() {
+    let week_anime_list = parse_schedule(FIXTURE_202410);
+    assert_eq!(
+        get_titles(&week_anime_list),
+        vec![
+            vec!["胆大党"],
+            vec![],
+            vec!["败犬女主太多了！"],
+            vec!["香格里拉·开拓异境 第二季", "Re:从零开始的异世界生活 第三季"],
+            vec!["地。-关于地球的运动-"],
+            vec![],
+            vec!["蓝色监狱 第二季"],
+        ]
+    );
+}
+
+#[test]
+#[ignore = "需要访问yuc.wiki"]
+fn parse_live_seasons() {
+    let source = YucWikiSource::new(DEFAULT_BASE_URL);
+    for suffix in ["202401", "202404", "202410"] {
+        let week_anime_list = source.fetch_season(suffix).unwrap().days;
+        assert_eq!(week_anime_list.len(), 7, "{suffix}");
+        let records = week_anime_list.iter().flatten().collect::<Vec<_>>();
+        assert!(
+            records.len() >= 20,
+            "{suffix}只解析出{}部番剧",
+            records.len()
+        );
+        assert!(records.iter().all(|r| !r.title.is_empty()), "{suffix}");
+    }
+}
+
+#[test]
+fn parse_skips_blocks_after_week() {
+    for fixture in [FIXTURE_202401, FIXTURE_202404, FIXTURE_202410] {
+        let week_anime_list = parse_schedule(fixture);
+        assert_eq!(week_anime_list.len(), 7);
+        assert!(
+            week_anime_list
+                .iter()
+                .flatten()
+                .all(|r| r.title != "剧场版 不应出现")
+        );
+    }
+}
+
+#[test]
+fn parse_metadata() {
+    let week_anime_list = parse_schedule(FIXTURE_202401);
+    assert_eq!(
+        find(&week_anime_list, "Fate/strange Fake"),
+        &AnimeRecord {
+            name: "Fate_strange Fake".to_string(),
+            title: "Fate/strange Fake".to_string(),
+            original_title: "Fate/strange Fake".to_string(),
+            broadcast_time: "1/8 0:00~".to_string(),
+            episodes: "全13话".to_string(),
+            studio: "A-1 Pictures".to_string(),
+            platforms: vec!["B站".to_string(), "爱奇艺".to_string()],
+            tags: vec!["奇幻".to_string(), "战斗".to_string()],
+            cover_url: "https://yuc.wiki/img/202401/fate.jpg".to_string(),
+        }
+    );
+    // 没有封面和播放平台时对应字段为空
+    let akogare = find(&week_anime_list, "憧憬成为魔法少女");
+    assert!(akogare.platforms.is_empty());
+    let dungeon = find(&week_anime_list, "迷宫饭");
+    assert_eq!(dungeon.cover_url, "");
+    assert_eq!(dungeon.original_title, "ダンジョン飯");
+}
+
+#[test]
+fn parse_invalid_filename_titles() {
+    let week_anime_list = parse_schedule(FIXTURE_202410);
+    let rezero = find(&week_anime_list, "Re:从零开始的异世界生活 第三季");
+    assert_eq!(rezero.name, "Re_从零开始的异世界生活 第三季");
+    assert_eq!(
+        rezero.original_title,
+        "Re:ゼロから始める異世界生活 3rd season"
+    );
+}
+
+#[test]
+fn parse_unrelated_page() {
+    let week_anime_list = parse_schedule("<html><body><p>404</p></body></html>");
+    assert!(week_anime_list.iter().all(|records| records.is_empty()));
+}
+
+#[test]
+fn valid_filename() {
+    assert_eq!(get_valid_filename("葬送的芙莉莲"), "葬送的芙莉莲");
+    assert_eq!(
+        get_valid_filename(r#"a/b\c:d*e?f"g<h>i|j"#),
+        "a_b_c_d_e_f_g_h_i_j"
+    );
+}
+
+#[test]
+fn suffix() {
+    let date = |year, month| SlintDate {
+        year,
+        month,
+        day: 1,
+    };
+    assert_eq!(get_suffix(date(2024, 1)), "202401");
+    assert_eq!(get_suffix(date(2024, 4)), "202404");
+    assert_eq!(get_suffix(date(2024, 7)), "202407");
+    assert_eq!(get_suffix(date(2024, 10)), "202410");
+}
+
+#[test]
+fn title_id_ignores_formatting() {
+    let id = get_title_id("Fate/strange Fake");
+    assert_eq!(get_title_id("Ｆａｔｅ／ｓｔｒａｎｇｅ　Ｆａｋｅ"), id);
+    assert_eq!(get_title_id(" fate strange fake "), id);
+    assert_eq!(get_title_id("葬送的芙莉莲！"), get_title_id("葬送的芙莉莲"));
+    assert_ne!(get_title_id("葬送的芙莉莲"), id);
+    assert_ne!(get_title_id("!!"), get_title_id("??"));
+}
@@ -0,0 +1,286 @@
+use crate::{
+    Anime, AnimeData, AppWindow, Todo, TodoKind, WatchStatus,
+    logic::{
+        APP_PATH, SlintDate,
+        anime::{get_season_range, get_suffix, record::get_title_id},
+        config::get_config,
+        storage::{load_json, save_json},
+        todo::{TODOS_MODEL, WEEKDAY, create_todo, refresh_todo_data},
+    },
+};
+use chrono::{DateTime, Local, NaiveDate};
+use serde::{Deserialize, Serialize};
+use slint::{ComponentHandle, Model, Weak};
+use std::{cell::RefCell, collections::BTreeMap, rc::Rc};
+
+thread_local! {
+    pub static WATCHLIST: Rc<RefCell<Watchlist>> = {
+    let path = APP_PATH.join("data").join("anime_watchlist.json");
+    let mut watchlist = load_json::<Watchlist>(&path).unwrap_or_default();
+    if watchlist.migrate_ids() {
+        watchlist.save_watchlist();
+    }
+    Rc::new(RefCell::new(watchlist))
+};
+}
+
+// 追番列表，按番剧id保存
+#[derive(Debug, Default, Serialize, Deserialize)]
+pub struct Watchlist {
+    #[serde(default)]
+    entries: BTreeMap<String, WatchEntry>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct WatchEntry {
+    // 方便查看文件内容，不参与匹配
+    pub title: String,
+    pub status: WatchStatusRecord,
+    #[serde(default)]
+    pub last_episode: i32,
+    #[serde(default)]
+    pub updated_at: String,
+    // 追番时创建的每周待办，每个季度创建一次
+    #[serde(default)]
+    pub todo_id: String,
+    #[serde(default)]
+    pub todo_season: String,
+}
+
+// 正在显示的季度，用来给追番创建待办
+struct Season {
+    suffix: String,
+    start: NaiveDate,
+    end: NaiveDate,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub enum WatchStatusRecord {
+    Following,
+    Dropped,
+    Completed,
+}
+
+impl Watchlist {
+    pub fn set_status(&mut self, anime: &Anime, status: WatchStatus) {
+        let status = match status {
+            WatchStatus::Unwatched => {
+                if let Some(entry) = self.entries.remove(anime.id.as_str()) {
+                    remove_anime_todo(&entry.todo_id);
+                }
+                self.save_watchlist();
+                return;
+            }
+            WatchStatus::Following => WatchStatusRecord::Following,
+            WatchStatus::Dropped => WatchStatusRecord::Dropped,
+            WatchStatus::Completed => WatchStatusRecord::Completed,
+        };
+        let entry = self.get_entry(anime);
+        entry.status = status;
+        entry.updated_at = Local::now().to_rfc3339();
+        self.save_watchlist();
+    }
+
+    // 不在列表中的番剧修改集数时自动加入追番
+    pub fn set_last_episode(&mut self, anime: &Anime, episode: i32) {
+        let entry = self.get_entry(anime);
+        entry.last_episode = episode.max(0);
+        entry.updated_at = Local::now().to_rfc3339();
+        self.save_watchlist();
+    }
+
+    pub fn apply_to_anime(&self, anime: &mut Anime) {
+        match self.entries.get(anime.id.as_str()) {
+            Some(entry) => {
+                anime.watch_status = entry.status.into();
+                anime.last_episode = entry.last_episode;
+            }
+            None => {
+                anime.watch_status = WatchStatus::Unwatched;
+                anime.last_episode = 0;
+            }
+        }
+    }
+
+    // 追番中的番剧在本季度还没有创建过待办时创建，不再追番时删除待办
+    // 用户手动删除的待办不会在同一季度重新创建
+    fn sync_anime_todo(&mut self, anime: &Anime, weekday: usize, season: &Season) -> bool {
+        let Some(entry) = self.entries.get_mut(anime.id.as_str()) else {
+            return false;
+        };
+        if entry.status != WatchStatusRecord::Following {
+            if entry.todo_id.is_empty() {
+                return false;
+            }
+            remove_anime_todo(&entry.todo_id);
+            entry.todo_id.clear();
+            entry.todo_season.clear();
+            self.save_watchlist();
+            return true;
+        }
+        if !get_config().anime_follow_todo
+            || entry.todo_season == season.suffix
+            || season.end < Local::now().date_naive()
+        {
+            return false;
+        }
+        let mut note = anime.broadcast_time.to_string();
+        if !anime.platforms.is_empty() {
+            note = format!("{} {}", note, anime.platforms).trim().to_string();
+        }
+        let todo = Todo {
+            text: format!("看番：{}", entry.title).into(),
+            kind: TodoKind::Weekly,
+            recurrence: true,
+            week: WEEKDAY[weekday].into(),
+            start_date: SlintDate::from_naive_date(&season.start),
+            end_date: SlintDate::from_naive_date(&season.end),
+            note: note.into(),
+            ..Default::default()
+        };
+        entry.todo_id = create_todo(todo).to_string();
+        entry.todo_season = season.suffix.clone();
+        self.save_watchlist();
+        true
+    }
+
+    // 旧版本的id由未规范化的标题计算，加载时按保存的标题重新计算，返回是否有变化
+    // 标题为空的记录当时使用的是番剧名称，无法重新计算，保持不变
+    // 规范化后相同的记录合并为一条
+    fn migrate_ids(&mut self) -> bool {
+        let mut changed = false;
+        for (id, entry) in std::mem::take(&mut self.entries) {
+            let new_id = if entry.title.is_empty() {
+                id.clone()
+            } else {
+                get_title_id(&entry.title)
+            };
+            changed |= new_id != id;
+            let entry = match self.entries.remove(&new_id) {
+                Some(other) => merge_entries(entry, other),
+                None => entry,
+            };
+            self.entries.insert(new_id, entry);
+        }
+        changed
+    }
+
+    fn get_entry(&mut self, anime: &Anime) -> &mut WatchEntry {
+        self.entries
+            .entry(anime.id.to_string())
+            .or_insert_with(|| WatchEntry {
+                title: anime.title.to_string(),
+                status: WatchStatusRecord::Following,
+                last_episode: 0,
+                updated_at: String::new(),
+                todo_id: String::new(),
+                todo_season: String::new(),
+            })
+    }
+
+    fn save_watchlist(&self) {
+        let path = APP_PATH.join("data").join("anime_watchlist.json");
+        if let Err(err) = save_json(&path, self) {
+            eprintln!("保存追番列表失败：{}", err);
+        }
+    }
+}
+
+impl From<WatchStatusRecord> for WatchStatus {
+    fn from(status: WatchStatusRecord) -> Self {
+        match status {
+            WatchStatusRecord::Following => WatchStatus::Following,
+            WatchStatusRecord::Dropped => WatchStatus::Dropped,
+            WatchStatusRecord::Completed => WatchStatus::Completed,
+        }
+    }
+}
+
+pub fn set_watchlist_logic(app: Weak<AppWindow>) {
+    let app = app.unwrap();
+    let anime_data = app.global::<AnimeData>();
+    let weak = app.as_weak();
+    anime_data.on_set_watch_status(move |anime: Anime, status: WatchStatus| {
+        WATCHLIST.with(|watchlist| watchlist.borrow_mut().set_status(&anime, status));
+        let app = weak.unwrap();
+        refresh_watch_status(&app);
+        sync_followed_todos(&app);
+        refresh_todo_data(&app);
+    });
+    let weak = app.as_weak();
+    anime_data.on_set_last_episode(move |anime: Anime, episode: i32| {
+        WATCHLIST.with(|watchlist| watchlist.borrow_mut().set_last_episode(&anime, episode));
+        refresh_watch_status(&weak.unwrap());
+    });
+}
+
+// 原地更新界面上的追番状态，不重新创建列表，避免滚动位置和封面重新加载
+pub fn refresh_watch_status(app: &AppWindow) {
+    let week_anime_list = app.global::<AnimeData>().get_week_anime_list();
+    for day_anime in week_anime_list.iter() {
+        let anime_list = day_anime.anime_list;
+        for i in 0..anime_list.row_count() {
+            if let Some(mut anime) = anime_list.row_data(i) {
+                WATCHLIST.with(|watchlist| watchlist.borrow().apply_to_anime(&mut anime));
+                anime_list.set_row_data(i, anime);
+            }
+        }
+    }
+}
+
+// 按正在显示的季度同步追番待办，季度加载完成和追番状态变化时调用
+pub fn sync_followed_todos(app: &AppWindow) {
+    let anime_data = app.global::<AnimeData>();
+    let anime_schedule = anime_data.get_anime_schedule();
+    let Some((start, end)) = get_season_range(&anime_schedule) else {
+        return;
+    };
+    let season = Season {
+        suffix: get_suffix(anime_schedule),
+        start,
+        end,
+    };
+    let mut changed = false;
+    // week-anime-list按周一到周日排列
+    for (weekday, day_anime) in anime_data.get_week_anime_list().iter().enumerate().take(7) {
+        for anime in day_anime.anime_list.iter() {
+            changed |= WATCHLIST.with(|watchlist| {
+                watchlist
+                    .borrow_mut()
+                    .sync_anime_todo(&anime, weekday, &season)
+            });
+        }
+    }
+    if changed {
+        refresh_todo_data(app);
+    }
+}
+
+// 保留最后修改的一条，另一条创建的待办在保留的一条没有待办时转给它，否则删除
+fn merge_entries(a: WatchEntry, b: WatchEntry) -> WatchEntry {
+    let updated_at = |entry: &WatchEntry| DateTime::parse_from_rfc3339(&entry.updated_at).ok();
+    let (mut newer, older) = if updated_at(&a) >= updated_at(&b) {
+        (a, b)
+    } else {
+        (b, a)
+    };
+    if newer.todo_id.is_empty() {
+        newer.todo_id = older.todo_id;
+        newer.todo_season = older.todo_season;
+    } else if older.todo_id != newer.todo_id {
+        remove_anime_todo(&older.todo_id);
+    }
+    newer
+}
+
+fn remove_anime_todo(todo_id: &str) {
+    if todo_id.is_empty() {
+        return;
+    }
+    TODOS_MODEL.with(|todos_model| {
+        let mut todos_model = todos_model.borrow_mut();
+        if todos_model.contains_todo(todo_id) {
+            todos_model.remove_todo_from_model(todo_id.into());
+        }
+    });
+}
@@ -0,0 +1,152 @@
+use crate::logic::{
+    anime::{
+        AnimeError, get_valid_filename,
+        record::AnimeRecord,
+        source::{ScheduleSource, SeasonPage, get_text},
+    },
+    normalize_text,
+};
+use scraper::{ElementRef, Html, Selector};
+
+// 长门有C（yuc.wiki）的季度新番页面，地址为<base_url><季度>
+pub struct YucWikiSource {
+    base_url: String,
+}
+
+impl YucWikiSource {
+    pub fn new(base_url: &str) -> Self {
+        YucWikiSource {
+            base_url: base_url.to_string(),
+        }
+    }
+}
+
+impl ScheduleSource for YucWikiSource {
+    fn name(&self) -> &'static str {
+        "长门有C"
+    }
+
+    fn fetch_season(&self, suffix: &str) -> Result<SeasonPage, AnimeError> {
+        let html = get_text(&format!("{}{}", self.base_url, suffix))?;
+        Ok(SeasonPage {
+            days: parse_schedule(&html),
+            page: html,
+        })
+    }
+}
+
+// 解析季度页面，返回周一到周日的番剧列表
+pub fn parse_schedule(html: &str) -> Vec<Vec<AnimeRecord>> {
+    let mut week_anime_list = vec![];
+    let document = Html::parse_document(html);
+    let selector = Selector::parse("div.post-body>div").unwrap();
+    document
+        .select(&selector)
+        .take(20)
+        .skip(1)
+        .step_by(3)
+        .for_each(|e| {
+            let name_selector = Selector::parse("tr:nth-child(1)>td").unwrap();
+            let date_selector = Selector::parse("div.div_date").unwrap();
+            // 封面和放送时间所在的div_date与标题表格按顺序一一对应
+            let dates = e.select(&date_selector).collect::<Vec<ElementRef>>();
+            let anime_list = e
+                .select(&name_selector)
+                .enumerate()
+                .map(|(i, ce)| {
+                    let mut record = parse_anime_table(ce);
+                    if let Some(date) = dates.get(i) {
+                        parse_anime_date(*date, &mut record);
+                    }
+                    record
+                })
+                .collect::<Vec<AnimeRecord>>();
+            week_anime_list.push(anime_list);
+        });
+    week_anime_list
+}
+
+// 从标题所在的表格中解析番剧信息，按单元格的class区分字段，找不到的字段留空
+fn parse_anime_table(title: ElementRef) -> AnimeRecord {
+    let r_str = title.text().collect::<String>();
+    let mut record = AnimeRecord {
+        // 文件名沿用原始文本，保证已下载的封面仍然能找到
+        name: get_valid_filename(&r_str),
+        title: normalize_text(&r_str),
+        ..Default::default()
+    };
+    let Some(table) = title
+        .ancestors()
+        .filter_map(ElementRef::wrap)
+        .find(|a| a.value().name() == "table")
+    else {
+        return record;
+    };
+    let td_selector = Selector::parse("td").unwrap();
+    let link_selector = Selector::parse("a").unwrap();
+    for td in table
+        .select(&td_selector)
+        .filter(|td| td.id() != title.id())
+    {
+        let class = td.value().attr("class").unwrap_or("");
+        let text = normalize_text(&td.text().collect::<String>());
+        if text.is_empty() {
+            continue;
+        }
+        if class.contains("area") || class.contains("platform") {
+            let links = td
+                .select(&link_selector)
+                .map(|a| normalize_text(&a.text().collect::<String>()))
+                .filter(|a| !a.is_empty())
+                .collect::<Vec<String>>();
+            record.platforms = if links.is_empty() {
+                split_list(&text)
+            } else {
+                links
+            };
+        } else if class.contains("tag") {
+            record.tags = split_list(&text);
+        } else if class.contains("staff") || class.contains("studio") || text.contains("制作") {
+            let studio = text
+                .split_once(['：', ':'])
+                .map(|(_, studio)| studio.trim())
+                .unwrap_or(&text);
+            record.studio = studio.to_string();
+        } else if class.contains("title") && record.original_title.is_empty() {
+            record.original_title = text;
+        }
+    }
+    record
+}
+
+// 从div_date中解析封面地址、放送时间和集数
+fn parse_anime_date(date: ElementRef, record: &mut AnimeRecord) {
+    let img_selector = Selector::parse("img").unwrap();
+    let p_selector = Selector::parse("p").unwrap();
+    if let Some(img) = date.select(&img_selector).next() {
+        let value = img.value();
+        record.cover_url = value
+            .attr("data-src")
+            .or(value.attr("src"))
+            .unwrap_or("")
+            .to_string();
+    }
+    for p in date.select(&p_selector) {
+        let class = p.value().attr("class").unwrap_or("");
+        // <br>分隔的文本用空格连接
+        let text = normalize_text(&p.text().collect::<Vec<&str>>().join(" "));
+        if class.contains("imgep") {
+            record.episodes = text;
+        } else if class.contains("imgtext") {
+            record.broadcast_time = text;
+        }
+    }
+}
+
+fn split_list(text: &str) -> Vec<String> {
+    text.split(['/', '、', ' '])
+        .map(str::trim)
+        .filter(|s| !s.is_empty())
+        .map(str::to_string)
+        .collect()
+}
@@ -0,0 +1,162 @@
+use crate::{
+    AppWindow, ProgressRecord, Todo, TodoData,
+    logic::{
+        APP_PATH, SlintDate,
+        config::{apply_config, get_config, update_config},
+        record::TodoRecord,
+        stats::StatsEntry,
+        storage::{load_json, save_json},
+        todo::{TODOS_MODEL, migrate_todo_ids, refresh_todo_data},
+    },
+};
+use serde::{Deserialize, Serialize};
+use slint::{ComponentHandle, ModelRc, SharedString, VecModel, Weak};
+use std::{cell::RefCell, rc::Rc};
+
+thread_local! {
+    pub static TODO_ARCHIVE: Rc<RefCell<TodoArchive>> = {
+    let path = APP_PATH.join("data").join("todo_archive.json");
+    let todo_archive = if let Some(mut todo_archive) = load_json::<TodoArchive>(&path) {
+        let migrated_ids = migrate_todo_ids(todo_archive.todos.iter_mut().map(|t| &mut t.todo));
+        if !migrated_ids.is_empty() {
+            todo_archive.save_archive();
+        }
+        todo_archive
+    } else {
+        TodoArchive::default()
+    };
+    Rc::new(RefCell::new(todo_archive))
+};
+}
+
+// 归档的待办连同完成记录和进度记录一起保存，恢复时不会丢失
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ArchivedTodo {
+    // 旧版本直接保存的界面Todo与TodoRecord字段一致，可以直接反序列化
+    pub todo: TodoRecord,
+    pub archived_at: String,
+    #[serde(default)]
+    pub done: Vec<SlintDate>,
+    #[serde(default)]
+    pub progress: Vec<ProgressRecord>,
+}
+
+#[derive(Debug, Default, Serialize, Deserialize)]
+pub struct TodoArchive {
+    #[serde(default)]
+    todos: Vec<ArchivedTodo>,
+}
+
+impl TodoArchive {
+    pub fn add_archived_todos(&mut self, todos: Vec<ArchivedTodo>) {
+        if todos.is_empty() {
+            return;
+        }
+        self.todos.extend(todos);
+        self.save_archive();
+    }
+
+    pub fn take_archived_todo(&mut self, id: SharedString) -> Option<ArchivedTodo> {
+        let index = self.todos.iter().position(|t| t.todo.id == id.as_str())?;
+        let archived_todo = self.todos.remove(index);
+        self.save_archive();
+        Some(archived_todo)
+    }
+
+    pub fn to_archived_list_model(&self) -> ModelRc<Todo> {
+        // 最近归档的排在前面
+        let todos = self
+            .todos
+            .iter()
+            .rev()
+            .map(|t| Todo::from(t.todo.clone()))
+            .collect::<Vec<Todo>>();
+        Rc::new(VecModel::from(todos)).into()
+    }
+
+    pub fn to_stats_entries(&self) -> Vec<StatsEntry> {
+        self.todos
+            .iter()
+            .map(|t| StatsEntry {
+                todo: Todo::from(t.todo.clone()),
+                done: t.done.iter().map(|d| d.to_naive_date()).collect(),
+                progress: t.progress.clone(),
+                archived: true,
+            })
+            .collect()
+    }
+
+    fn save_archive(&self) {
+        let path = APP_PATH.join("data").join("todo_archive.json");
+        if let Err(err) = save_json(&path, self) {
+            eprintln!("保存归档失败：{}", err);
+        }
+    }
+}
+
+pub fn set_archive_logic(app: Weak<AppWindow>) {
+    let app = app.unwrap();
+    let todo_data = app.global::<TodoData>();
+    let weak = app.as_weak();
+    todo_data.on_archive_todos(move || archive_todos(weak.clone()));
+    let weak = app.as_weak();
+    todo_data.on_restore_todo(move |id: SharedString| restore_todo(id, weak.clone()));
+    let weak = app.as_weak();
+    todo_data
+        .on_remove_archived_todo(move |id: SharedString| remove_archived_todo(id, weak.clone()));
+    let weak = app.as_weak();
+    todo_data.on_update_auto_archive(move |auto_archive: bool| {
+        update_config(|config| config.auto_archive = auto_archive);
+        apply_config(&weak.unwrap());
+    });
+}
+
+fn archive_todos(app: Weak<AppWindow>) {
+    let app = app.unwrap();
+    let archived_todos =
+        TODOS_MODEL.with(|todos_model| todos_model.borrow_mut().take_archivable_todos());
+    TODO_ARCHIVE.with(|todo_archive| todo_archive.borrow_mut().add_archived_todos(archived_todos));
+    refresh_todo_data(&app);
+    refresh_archive_data(&app);
+}
+
+fn restore_todo(id: SharedString, app: Weak<AppWindow>) {
+    let app = app.unwrap();
+    let archived_todo =
+        TODO_ARCHIVE.with(|todo_archive| todo_archive.borrow_mut().take_archived_todo(id));
+    if let Some(archived_todo) = archived_todo {
+        TODOS_MODEL.with(|todos_model| {
+            todos_model
+                .borrow_mut()
+                .restore_todo_to_model(archived_todo)
+        });
+    }
+    refresh_todo_data(&app);
+    refresh_archive_data(&app);
+}
+
+fn remove_archived_todo(id: SharedString, app: Weak<AppWindow>) {
+    let app = app.unwrap();
+    TODO_ARCHIVE.with(|todo_archive| todo_archive.borrow_mut().take_archived_todo(id));
+    refresh_archive_data(&app);
+}
+
+fn refresh_archive_data(app: &AppWindow) {
+    let todo_data = app.global::<TodoData>();
+    todo_data.set_archived_todo_list(
+        TODO_ARCHIVE.with(|todo_archive| todo_archive.borrow().to_archived_list_model()),
+    );
+}
+
+// 需要在init_todos之前调用，保证界面初始化时已经完成自动归档
+pub fn init_archive(app: Weak<AppWindow>) {
+    let app = app.unwrap();
+    // 是否自动归档由配置决定
+    if get_config().auto_archive {
+        let archived_todos =
+            TODOS_MODEL.with(|todos_model| todos_model.borrow_mut().take_archivable_todos());
+        TODO_ARCHIVE
+            .with(|todo_archive| todo_archive.borrow_mut().add_archived_todos(archived_todos));
+    }
+    refresh_archive_data(&app);
+}
@@ -0,0 +1,307 @@
+use crate::{
+    AppWindow, Setting, SettingData, TodoData, UiData,
+    logic::{
+        APP_PATH,
+        network::reset_client,
+        storage::{load_json, save_json},
+    },
+};
+use serde::{Deserialize, Serialize};
+use slint::{Color, ComponentHandle, LogicalSize, Weak};
+use std::sync::{LazyLock, RwLock};
+
+// 番剧数据源的默认地址
+pub const DEFAULT_BASE_URL: &str = "https://yuc.wiki/";
+pub const DEFAULT_USER_AGENT: &str =
+    concat!(env!("CARGO_PKG_NAME"), "/", env!("CARGO_PKG_VERSION"));
+// 提醒最多提前一周
+const MAX_LEAD_MINUTES: i32 = 7 * 24 * 60;
+
+// 后台线程也需要读取配置，所以用RwLock而不是thread_local
+pub static CONFIG: LazyLock<RwLock<Config>> = LazyLock::new(|| {
+    let path = APP_PATH.join("config.json");
+    let config = match load_json::<Config>(&path) {
+        Some(config) => config,
+        None => {
+            let config = Config::default();
+            config.save_config();
+            config
+        }
+    };
+    RwLock::new(config)
+});
+
+// 配置文件，新增字段时需要在Default中给出默认值，旧的配置文件缺少的字段会使用默认值
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct Config {
+    pub app_width: i32,
+    pub app_height: i32,
+    pub cover_width: i32,
+    pub cover_height: i32,
+    // 封面缓存的容量上限，超出时删除最久没有使用的封面
+    pub cover_cache_limit_mb: i32,
+    pub default_font_size: i32,
+    pub primary_color: String,
+    pub warn_color: String,
+    pub anime_base_url: String,
+    pub anime_source: AnimeSourceKind,
+    pub anime_local_dir: String,
+    pub anime_mirror_url: String,
+    // 当前季度番剧表的刷新间隔，过去的季度不会刷新
+    pub anime_cache_ttl_hours: i32,
+    // 追番时是否创建每周的看番待办
+    pub anime_follow_todo: bool,
+    pub auto_archive: bool,
+    // 代理地址，支持http、https和socks5，为空时不使用代理
+    pub proxy_url: String,
+    // 额外信任的PEM格式证书文件，为空时只使用系统证书
+    pub ca_bundle_path: String,
+    pub request_timeout_secs: i32,
+    pub user_agent: String,
+    pub reminder_enabled: bool,
+    // 除了到时间时，还要提前多少分钟提醒
+    pub reminder_lead_minutes: Vec<i32>,
+    // 新闻订阅源，在新闻页面管理，不在设置页面显示
+    pub news_feeds: Vec<NewsFeedConfig>,
+}
+
+// 番剧数据源，顺序与设置页面的下拉框一致
+#[derive(Debug, Clone, Copy, Default, PartialEq, Serialize, Deserialize)]
+pub enum AnimeSourceKind {
+    #[default]
+    YucWiki,
+    Local,
+    JsonMirror,
+}
+
+// 一个RSS或Atom订阅源，名称为空时显示订阅源自己的标题
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct NewsFeedConfig {
+    pub name: String,
+    pub url: String,
+}
+
+impl Default for Config {
+    fn default() -> Self {
+        Config {
+            app_width: 1280,
+            app_height: 800,
+            cover_width: 120,
+            cover_height: 150,
+            cover_cache_limit_mb: 200,
+            default_font_size: 14,
+            primary_color: "#6AB187".to_string(),
+            warn_color: "#FF5252".to_string(),
+            anime_base_url: DEFAULT_BASE_URL.to_string(),
+            anime_source: AnimeSourceKind::YucWiki,
+            anime_local_dir: String::new(),
+            anime_mirror_url: String::new(),
+            anime_cache_ttl_hours: 24,
+            anime_follow_todo: false,
+            auto_archive: false,
+            proxy_url: String::new(),
+            ca_bundle_path: String::new(),
+            request_timeout_secs: 15,
+            user_agent: DEFAULT_USER_AGENT.to_string(),
+            reminder_enabled: true,
+            reminder_lead_minutes: vec![10],
+            news_feeds: vec![],
+        }
+    }
+}
+
+impl Config {
+    fn save_config(&self) {
+        let path = APP_PATH.join("config.json");
+        if let Err(err) = save_json(&path, self) {
+            eprintln!("保存配置失败：{}", err);
+        }
+    }
+
+    fn to_setting(&self) -> Setting {
+        Setting {
+            app_width: self.app_width,
+            app_height: self.app_height,
+            cover_width: self.cover_width,
+            cover_height: self.cover_height,
+            cover_cache_limit_mb: self.cover_cache_limit_mb,
+            default_font_size: self.default_font_size,
+            primary_color: self.primary_color.clone().into(),
+            warn_color: self.warn_color.clone().into(),
+            anime_base_url: self.anime_base_url.clone().into(),
+            anime_source: self.anime_source as i32,
+            anime_local_dir: self.anime_local_dir.clone().into(),
+            anime_mirror_url: self.anime_mirror_url.clone().into(),
+            anime_cache_ttl_hours: self.anime_cache_ttl_hours,
+            anime_follow_todo: self.anime_follow_todo,
+            auto_archive: self.auto_archive,
+            proxy_url: self.proxy_url.clone().into(),
+            ca_bundle_path: self.ca_bundle_path.clone().into(),
+            request_timeout_secs: self.request_timeout_secs,
+            user_agent: self.user_agent.clone().into(),
+            reminder_enabled: self.reminder_enabled,
+            reminder_lead_minutes: self
+                .reminder_lead_minutes
+                .iter()
+                .map(|lead| lead.to_string())
+                .collect::<Vec<String>>()
+                .join(", ")
+                .into(),
+        }
+    }
+
+    // 界面上修改的设置，不合法的值保持原来的配置
+    fn merge_setting(&mut self, setting: Setting) {
+        self.app_width = setting.app_width.max(800);
+        self.app_height = setting.app_height.max(600);
+        self.cover_width = setting.cover_width.max(1);
+        self.cover_height = setting.cover_height.max(1);
+        self.cover_cache_limit_mb = setting.cover_cache_limit_mb.max(1);
+        self.default_font_size = setting.default_font_size.max(1);
+        if parse_color(&setting.primary_color).is_some() {
+            self.primary_color = setting.primary_color.to_string();
+        }
+        if parse_color(&setting.warn_color).is_some() {
+            self.warn_color = setting.warn_color.to_string();
+        }
+        if !setting.anime_base_url.trim().is_empty() {
+            self.anime_base_url = normalize_url(&setting.anime_base_url);
+        }
+        self.anime_source = match setting.anime_source {
+            1 => AnimeSourceKind::Local,
+            2 => AnimeSourceKind::JsonMirror,
+            _ => AnimeSourceKind::YucWiki,
+        };
+        self.anime_local_dir = setting.anime_local_dir.trim().to_string();
+        // 镜像地址允许为空，为空时不使用镜像
+        self.anime_mirror_url = if setting.anime_mirror_url.trim().is_empty() {
+            String::new()
+        } else {
+            normalize_url(&setting.anime_mirror_url)
+        };
+        self.anime_cache_ttl_hours = setting.anime_cache_ttl_hours.max(1);
+        self.anime_follow_todo = setting.anime_follow_todo;
+        self.auto_archive = setting.auto_archive;
+        let proxy_url = setting.proxy_url.trim();
+        if proxy_url.is_empty() || reqwest::Proxy::all(proxy_url).is_ok() {
+            self.proxy_url = proxy_url.to_string();
+        }
+        self.ca_bundle_path = setting.ca_bundle_path.trim().to_string();
+        self.request_timeout_secs = setting.request_timeout_secs.max(1);
+        self.user_agent = match setting.user_agent.trim() {
+            "" => DEFAULT_USER_AGENT.to_string(),
+            user_agent => user_agent.to_string(),
+        };
+        self.reminder_enabled = setting.reminder_enabled;
+        self.reminder_lead_minutes = parse_lead_minutes(&setting.reminder_lead_minutes);
+    }
+}
+
+// 修改配置并保存
+pub fn update_config(f: impl FnOnce(&mut Config)) {
+    let mut config = CONFIG.write().unwrap();
+    f(&mut config);
+    config.save_config();
+}
+
+pub fn get_config() -> Config {
+    CONFIG.read().unwrap().clone()
+}
+
+// 地址以/结尾，方便直接拼接季度
+fn normalize_url(url: &str) -> String {
+    let mut url = url.trim().to_string();
+    if !url.ends_with('/') {
+        url.push('/');
+    }
+    url
+}
+
+// 逗号或空格分隔的分钟数，忽略不合法的值，从大到小排列
+fn parse_lead_minutes(text: &str) -> Vec<i32> {
+    let mut leads = text
+        .split([',', '，', ' '])
+        .filter_map(|lead| lead.trim().parse::<i32>().ok())
+        .filter(|lead| (1..=MAX_LEAD_MINUTES).contains(lead))
+        .collect::<Vec<i32>>();
+    leads.sort_by(|a, b| b.cmp(a));
+    leads.dedup();
+    leads
+}
+
+// 解析#RRGGBB格式的颜色
+fn parse_color(color: &str) -> Option<Color> {
+    let hex = color.trim().strip_prefix('#')?;
+    if hex.len() != 6 || !hex.chars().all(|c| c.is_ascii_hexdigit()) {
+        return None;
+    }
+    let value = u32::from_str_radix(hex, 16).ok()?;
+    Some(Color::from_rgb_u8(
+        (value >> 16) as u8,
+        (value >> 8) as u8,
+        value as u8,
+    ))
+}
+
+pub fn set_setting_logic(app: Weak<AppWindow>) {
+    let app = app.unwrap();
+    let setting_data = app.global::<SettingData>();
+    let weak = app.as_weak();
+    setting_data.on_update_setting(move |setting: Setting| {
+        update_config(|config| config.merge_setting(setting));
+        reset_client();
+        let app = weak.unwrap();
+        apply_config(&app);
+        apply_window_size(&app);
+    });
+    let weak = app.as_weak();
+    setting_data.on_reset_setting(move || {
+        // 订阅源不属于设置页面，恢复默认设置时保留
+        update_config(|config| {
+            *config = Config {
+                news_feeds: std::mem::take(&mut config.news_feeds),
+                ..Config::default()
+            }
+        });
+        reset_client();
+        let app = weak.unwrap();
+        apply_config(&app);
+        apply_window_size(&app);
+    });
+}
+
+// 把配置推送到界面上
+pub fn apply_config(app: &AppWindow) {
+    let config = get_config();
+    let ui_data = app.global::<UiData>();
+    ui_data.set_app_width(config.app_width as f32);
+    ui_data.set_app_height(config.app_height as f32);
+    ui_data.set_cover_width(config.cover_width as f32);
+    ui_data.set_cover_height(config.cover_height as f32);
+    ui_data.set_default_font_size(config.default_font_size as f32);
+    if let Some(color) = parse_color(&config.primary_color) {
+        ui_data.set_primary_color(color);
+    }
+    if let Some(color) = parse_color(&config.warn_color) {
+        ui_data.set_warn_color(color);
+    }
+    app.global::<TodoData>()
+        .set_auto_archive(config.auto_archive);
+    app.global::<SettingData>().set_setting(config.to_setting());
+}
+
+fn apply_window_size(app: &AppWindow) {
+    let config = get_config();
+    app.window().set_size(LogicalSize::new(
+        config.app_width as f32,
+        config.app_height as f32,
+    ));
+}
+
+pub fn init_config(app: Weak<AppWindow>) {
+    let app = app.unwrap();
+    apply_config(&app);
+    apply_window_size(&app);
+}
@@ -0,0 +1,95 @@
+use crate::{
+    AppWindow, HomeData, Todo, TodoData, TodoKind,
+    logic::{
+        SlintDate,
+        todo::{create_todo, get_today, refresh_todo_data},
+    },
+};
+use chrono::{Datelike, Days, NaiveDate};
+use slint::{ComponentHandle, Model, ModelRc, SharedString, VecModel, Weak};
+use std::rc::Rc;
+
+// 主页显示未来几天内的待办
+const UPCOMING_DAYS: u64 = 7;
+
+pub fn set_home_logic(app: Weak<AppWindow>) {
+    let app = app.unwrap();
+    let home_data = app.global::<HomeData>();
+    home_data.on_overdue_todos(overdue_todos);
+    home_data.on_upcoming_todos(upcoming_todos);
+    let weak = app.as_weak();
+    home_data.on_quick_add(move |text: SharedString| quick_add(text, weak.clone()));
+    let weak = app.as_weak();
+    home_data.on_refresh_weekday(move || refresh_weekday(&weak.unwrap()));
+}
+
+// 已经过了日期还没有完成的单次待办和进度待办，按日期从早到晚排列
+fn overdue_todos(model: ModelRc<Todo>) -> ModelRc<Todo> {
+    let mut todos = model
+        .iter()
+        .filter(|t| !t.is_done)
+        .filter_map(|t| {
+            let date = match t.kind {
+                TodoKind::Once => t.once.to_naive_date(),
+                TodoKind::Progress => t.end_date.to_naive_date(),
+                _ => return None,
+            };
+            (date < get_today()).then_some((date, t))
+        })
+        .collect::<Vec<(NaiveDate, Todo)>>();
+    todos.sort_by_key(|(date, _)| *date);
+    let todos = todos.into_iter().map(|(_, t)| t).collect::<Vec<Todo>>();
+    Rc::new(VecModel::from(todos)).into()
+}
+
+// 明天起UPCOMING_DAYS天内要做的待办，days_to_start改为距离下一次的天数并按它排序
+fn upcoming_todos(model: ModelRc<Todo>) -> ModelRc<Todo> {
+    let mut todos = model
+        .iter()
+        // 单次和进度待办完成后就不用再做了，重复待办的完成状态只对应今天
+        .filter(|t| !(t.is_done && matches!(t.kind, TodoKind::Once | TodoKind::Progress)))
+        .filter_map(|mut t| {
+            let days = (1..=UPCOMING_DAYS).find(|days| {
+                get_today()
+                    .checked_add_days(Days::new(*days))
+                    .is_some_and(|date| t.occurs_on(date))
+            })?;
+            t.days_to_start = days as i32;
+            t.is_done = false;
+            Some(t)
+        })
+        .collect::<Vec<Todo>>();
+    // 排序是稳定的，同一天的待办保持创建顺序
+    todos.sort_by_key(|t| t.days_to_start);
+    Rc::new(VecModel::from(todos)).into()
+}
+
+// 快速添加今天的单次待办
+fn quick_add(text: SharedString, app: Weak<AppWindow>) {
+    let text = text.trim();
+    if text.is_empty() {
+        return;
+    }
+    let app = app.unwrap();
+    let today = SlintDate::from_naive_date(&get_today());
+    let todo = Todo {
+        text: text.into(),
+        kind: TodoKind::Once,
+        recurrence: false,
+        once: today.clone(),
+        start_date: today.clone(),
+        end_date: today,
+        ..app.global::<TodoData>().get_default_todo()
+    };
+    create_todo(todo);
+    refresh_todo_data(&app);
+}
+
+fn refresh_weekday(app: &AppWindow) {
+    app.global::<HomeData>()
+        .set_weekday(get_today().weekday().num_days_from_monday() as i32);
+}
+
+pub fn init_home(app: Weak<AppWindow>) {
+    refresh_weekday(&app.unwrap());
+}
@@ -1,31 +1,122 @@
 use crate::AppWindow;
 use slint::Weak;
-use std::path::PathBuf;
+use std::path::{Path, PathBuf};
 use std::sync::LazyLock;
 
-pub const APP_PATH: LazyLock<PathBuf> = LazyLock::new(|| {
-    let path = std::env::var("APPDATA").unwrap();
-    let app_path = PathBuf::from(path).join(".ttd_v4");
+// 指定数据目录的环境变量和命令行参数，优先级为命令行参数 > 环境变量 > 系统默认目录
+const DATA_DIR_ENV: &str = "TTD_V4_DATA_DIR";
+const DATA_DIR_ARG: &str = "--data-dir";
+// 旧版本的数据目录名，Windows下继续沿用
+const LEGACY_DIR_NAME: &str = ".ttd_v4";
+const DIR_NAME: &str = "ttd_v4";
+
+pub static APP_PATH: LazyLock<PathBuf> = LazyLock::new(|| {
+    let app_path = get_app_path();
+    if !app_path.exists() {
+        migrate_legacy_dir(&app_path);
+    }
     if !app_path.exists() {
-        std::fs::create_dir(&app_path).unwrap();
+        std::fs::create_dir_all(&app_path).unwrap();
     }
     app_path
 });
 
+fn get_app_path() -> PathBuf {
+    let mut args = std::env::args().skip(1);
+    while let Some(arg) = args.next() {
+        if arg == DATA_DIR_ARG
+            && let Some(path) = args.next()
+        {
+            return PathBuf::from(path);
+        } else if let Some(path) = arg.strip_prefix(&format!("{}=", DATA_DIR_ARG)) {
+            return PathBuf::from(path);
+        }
+    }
+    if let Some(path) = std::env::var_os(DATA_DIR_ENV).filter(|p| !p.is_empty()) {
+        return PathBuf::from(path);
+    }
+    get_default_app_path()
+}
+
+// Windows下为%APPDATA%，Linux下为$XDG_DATA_HOME或~/.local/share，macOS下为~/Library/Application Support
+fn get_default_app_path() -> PathBuf {
+    let data_dir = dirs::data_dir()
+        .or_else(|| dirs::home_dir().map(|p| p.join(".local").join("share")))
+        .expect("无法确定数据目录，请通过--data-dir或TTD_V4_DATA_DIR指定");
+    if cfg!(windows) {
+        data_dir.join(LEGACY_DIR_NAME)
+    } else {
+        data_dir.join(DIR_NAME)
+    }
+}
+
+// 把旧版本的.ttd_v4目录迁移到新的数据目录
+fn migrate_legacy_dir(app_path: &Path) {
+    let mut legacy_paths = vec![];
+    if let Some(path) = std::env::var_os("APPDATA") {
+        legacy_paths.push(PathBuf::from(path).join(LEGACY_DIR_NAME));
+    }
+    if let Some(path) = dirs::data_dir() {
+        legacy_paths.push(path.join(LEGACY_DIR_NAME));
+    }
+    if let Some(path) = dirs::home_dir() {
+        legacy_paths.push(path.join(LEGACY_DIR_NAME));
+    }
+    let Some(legacy_path) = legacy_paths
+        .into_iter()
+        .find(|p| p.is_dir() && p != app_path)
+    else {
+        return;
+    };
+    if let Some(parent) = app_path.parent() {
+        let _ = std::fs::create_dir_all(parent);
+    }
+    // 跨磁盘时无法重命名，改为复制，旧目录保留
+    if std::fs::rename(&legacy_path, app_path).is_err()
+        && let Err(err) = copy_dir(&legacy_path, app_path)
+    {
+        eprintln!(
+            "迁移{}到{}失败：{}",
+            legacy_path.display(),
+            app_path.display(),
+            err
+        );
+    }
+}
+
+fn copy_dir(from: &Path, to: &Path) -> std::io::Result<()> {
+    std::fs::create_dir_all(to)?;
+    for entry in std::fs::read_dir(from)? {
+        let entry = entry?;
+        let target = to.join(entry.file_name());
+        if entry.file_type()?.is_dir() {
+            copy_dir(&entry.path(), &target)?;
+        } else {
+            std::fs::copy(entry.path(), target)?;
+        }
+    }
+    Ok(())
+}
+
 pub fn init(app: Weak<AppWindow>) {
     check_data_dir();
+    crate::logic::init_config(app.clone());
     let anime_schedule = crate::logic::init_anime_schedule(app.clone());
-    crate::logic::get_anime(app.clone(), anime_schedule);
+    crate::logic::get_anime(app.clone(), anime_schedule, false);
+    crate::logic::init_archive(app.clone());
     crate::logic::init_todos(app.clone());
+    crate::logic::init_home(app.clone());
+    crate::logic::init_news(app.clone());
+    crate::logic::init_reminders(app.clone());
 }
 
 fn check_data_dir() {
     let anime_path = APP_PATH.join("covers");
     if !anime_path.exists() {
-        std::fs::create_dir(&anime_path).unwrap();
+        std::fs::create_dir_all(&anime_path).unwrap();
     }
     let data_path = APP_PATH.join("data");
     if !data_path.exists() {
-        std::fs::create_dir(&data_path).unwrap();
+        std::fs::create_dir_all(&data_path).unwrap();
     }
 }
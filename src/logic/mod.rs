@@ -1,13 +1,28 @@
 use chrono::{Datelike, NaiveDate, Weekday};
 mod anime;
+mod archive;
+mod config;
+mod home;
 mod init;
+mod network;
+mod news;
+mod record;
+mod reminder;
+mod stats;
+mod storage;
 mod todo;
 
 pub use crate::Date as SlintDate;
 use crate::SlintWeekday;
 pub use anime::{get_anime, init_anime_schedule, set_anime_logic};
+pub use archive::{init_archive, set_archive_logic};
+pub use config::{init_config, set_setting_logic};
+pub use home::{init_home, set_home_logic};
 pub use init::{APP_PATH, init};
+pub use news::{init_news, set_news_logic};
+pub use reminder::{init_reminders, save_reminders, set_reminder_logic};
 use serde::{Deserialize, Serialize};
+pub use stats::set_stats_logic;
 pub use todo::{init_todos, set_todo_logic};
 
 impl Serialize for SlintDate {
@@ -39,21 +54,7 @@ impl<'de> Deserialize<'de> for SlintDate {
 
 impl PartialOrd for SlintDate {
     fn partial_cmp(&self, other: &Self) -> Option<std::cmp::Ordering> {
-        if self.year < other.year {
-            return Some(std::cmp::Ordering::Less);
-        } else if self.year > other.year {
-            return Some(std::cmp::Ordering::Greater);
-        } else if self.month < other.month {
-            return Some(std::cmp::Ordering::Less);
-        } else if self.month > other.month {
-            return Some(std::cmp::Ordering::Greater);
-        } else if self.day < other.day {
-            return Some(std::cmp::Ordering::Less);
-        } else if self.day > other.day {
-            return Some(std::cmp::Ordering::Greater);
-        } else {
-            return Some(std::cmp::Ordering::Equal);
-        }
+        Some((self.year, self.month, self.day).cmp(&(other.year, other.month, other.day)))
     }
 }
 
@@ -99,3 +100,8 @@ impl From<SlintWeekday> for Weekday {
         }
     }
 }
+
+// 合并连续的空白字符，解析网页和订阅源时使用
+pub fn normalize_text(text: &str) -> String {
+    text.split_whitespace().collect::<Vec<&str>>().join(" ")
+}
@@ -0,0 +1,89 @@
+use crate::logic::config::{Config, get_config};
+use reqwest::{Certificate, Client, Proxy};
+use std::{
+    fmt::{self, Display},
+    sync::{LazyLock, RwLock},
+    time::Duration,
+};
+use tokio::runtime::Runtime;
+
+// 所有网络请求共用的线程池，后台线程通过block_on等待请求完成
+pub static RUNTIME: LazyLock<Runtime> = LazyLock::new(|| {
+    tokio::runtime::Builder::new_multi_thread()
+        .worker_threads(2)
+        .thread_name("network")
+        .enable_all()
+        .build()
+        .expect("网络线程池创建失败")
+});
+// 按当前配置创建的客户端，修改设置后清空，下次使用时重新创建
+static CLIENT: RwLock<Option<Client>> = RwLock::new(None);
+
+#[derive(Debug)]
+pub enum NetworkError {
+    Io(std::io::Error),
+    Request(reqwest::Error),
+}
+
+impl Display for NetworkError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            NetworkError::Io(err) => write!(f, "证书文件读取失败：{}", err),
+            NetworkError::Request(err) => write!(f, "网络请求失败：{}", err),
+        }
+    }
+}
+
+impl From<std::io::Error> for NetworkError {
+    fn from(err: std::io::Error) -> Self {
+        NetworkError::Io(err)
+    }
+}
+
+impl From<reqwest::Error> for NetworkError {
+    fn from(err: reqwest::Error) -> Self {
+        NetworkError::Request(err)
+    }
+}
+
+// 代理、证书、超时和User-Agent都在这里设置，所有网络请求都使用这个客户端
+fn build_client(config: &Config) -> Result<Client, NetworkError> {
+    // 超时包括建立连接的时间，代理较慢时调大request_timeout_secs即可
+    let timeout = Duration::from_secs(config.request_timeout_secs.max(1) as u64);
+    let mut builder = Client::builder()
+        .user_agent(&config.user_agent)
+        .timeout(timeout);
+    if !config.proxy_url.is_empty() {
+        builder = builder.proxy(Proxy::all(&config.proxy_url)?);
+    }
+    if !config.ca_bundle_path.is_empty() {
+        let pem = std::fs::read(&config.ca_bundle_path)?;
+        for cert in Certificate::from_pem_bundle(&pem)? {
+            builder = builder.add_root_certificate(cert);
+        }
+    }
+    Ok(builder.build()?)
+}
+
+pub fn get_client() -> Result<Client, NetworkError> {
+    if let Some(client) = CLIENT.read().unwrap().as_ref() {
+        return Ok(client.clone());
+    }
+    let client = build_client(&get_config())?;
+    *CLIENT.write().unwrap() = Some(client.clone());
+    Ok(client)
+}
+
+// 网络相关的设置修改后调用
+pub fn reset_client() {
+    *CLIENT.write().unwrap() = None;
+}
+
+// 在后台线程中获取文本内容，不能在RUNTIME的线程中调用
+pub fn get_text(url: &str) -> Result<String, NetworkError> {
+    let client = get_client()?;
+    RUNTIME.block_on(async {
+        let response = client.get(url).send().await?.error_for_status()?;
+        Ok(response.text().await?)
+    })
+}
@@ -0,0 +1,174 @@
+use crate::logic::normalize_text;
+use chrono::DateTime;
+use roxmltree::{Document, Node, ParsingOptions};
+use scraper::Html;
+use serde::{Deserialize, Serialize};
+
+// 摘要最多保留的字符数
+const SUMMARY_LENGTH: usize = 200;
+// 发布时间统一转换为这个格式，无法识别的保持原样
+pub const PUBLISHED_FORMAT: &str = "%Y-%m-%d %H:%M";
+
+// 订阅源中的一条新闻，id在同一个订阅源中唯一，用来记录是否已读
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct FeedItem {
+    pub id: String,
+    pub title: String,
+    pub link: String,
+    pub published: String,
+    pub summary: String,
+}
+
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct ParsedFeed {
+    pub title: String,
+    pub items: Vec<FeedItem>,
+}
+
+// 支持RSS 2.0、RSS 1.0和Atom，只按元素的本地名称匹配，不检查命名空间
+pub fn parse_feed(xml: &str) -> Result<ParsedFeed, String> {
+    // 不少RSS 0.91的订阅源带有DOCTYPE，roxmltree默认不允许DTD
+    let options = ParsingOptions {
+        allow_dtd: true,
+        ..Default::default()
+    };
+    let document = Document::parse_with_options(xml, options).map_err(|e| e.to_string())?;
+    let root = document.root_element();
+    match root.tag_name().name() {
+        "rss" | "RDF" => {
+            let channel = find_child(root, "channel").ok_or("RSS缺少channel")?;
+            // RSS 1.0的item和channel平级，RSS 2.0的item在channel里面
+            let items = channel
+                .children()
+                .chain(root.children())
+                .filter(|n| n.has_tag_name("item"))
+                .map(parse_rss_item)
+                .collect();
+            Ok(ParsedFeed {
+                title: get_child_text(channel, "title"),
+                items,
+            })
+        }
+        "feed" => Ok(ParsedFeed {
+            title: get_child_text(root, "title"),
+            items: root
+                .children()
+                .filter(|n| n.has_tag_name("entry"))
+                .map(parse_atom_entry)
+                .collect(),
+        }),
+        name => Err(format!("不是RSS或Atom订阅源：{}", name)),
+    }
+}
+
+fn parse_rss_item(node: Node) -> FeedItem {
+    let link = get_child_text(node, "link");
+    // RSS 1.0的item用rdf:about作为标识
+    let about = node
+        .attributes()
+        .find(|a| a.name() == "about")
+        .map(|a| a.value().to_string())
+        .unwrap_or_default();
+    let published = ["pubDate", "date"]
+        .iter()
+        .map(|name| get_child_text(node, name))
+        .find(|date| !date.is_empty())
+        .unwrap_or_default();
+    new_item(
+        [get_child_text(node, "guid"), about],
+        get_child_text(node, "title"),
+        link,
+        &published,
+        &get_child_text(node, "description"),
+    )
+}
+
+fn parse_atom_entry(node: Node) -> FeedItem {
+    // 没有rel的link等同于rel="alternate"
+    let link = node
+        .children()
+        .filter(|n| n.has_tag_name("link"))
+        .find(|n| n.attribute("rel").is_none_or(|rel| rel == "alternate"))
+        .and_then(|n| n.attribute("href"))
+        .unwrap_or_default()
+        .to_string();
+    let published = ["published", "updated"]
+        .iter()
+        .map(|name| get_child_text(node, name))
+        .find(|date| !date.is_empty())
+        .unwrap_or_default();
+    let summary = ["summary", "content"]
+        .iter()
+        .map(|name| get_child_text(node, name))
+        .find(|summary| !summary.is_empty())
+        .unwrap_or_default();
+    new_item(
+        [get_child_text(node, "id"), String::new()],
+        get_child_text(node, "title"),
+        link,
+        &published,
+        &summary,
+    )
+}
+
+// 依次使用ids、链接和标题作为id
+fn new_item(
+    ids: [String; 2],
+    title: String,
+    link: String,
+    published: &str,
+    summary: &str,
+) -> FeedItem {
+    let id = ids
+        .into_iter()
+        .chain([link.clone(), title.clone()])
+        .find(|id| !id.is_empty())
+        .unwrap_or_default();
+    FeedItem {
+        id,
+        title: normalize_text(&title),
+        link,
+        published: format_published(published),
+        summary: to_summary(summary),
+    }
+}
+
+fn find_child<'a, 'input>(node: Node<'a, 'input>, name: &str) -> Option<Node<'a, 'input>> {
+    node.children().find(|n| n.has_tag_name(name))
+}
+
+// 子元素的文本，包括CDATA，没有时为空
+fn get_child_text(node: Node, name: &str) -> String {
+    find_child(node, name)
+        .map(|n| {
+            n.descendants()
+                .filter(|d| d.is_text())
+                .filter_map(|d| d.text())
+                .collect::<String>()
+                .trim()
+                .to_string()
+        })
+        .unwrap_or_default()
+}
+
+// RSS使用RFC 2822，Atom使用RFC 3339，按订阅源给出的时区显示
+fn format_published(published: &str) -> String {
+    DateTime::parse_from_rfc2822(published)
+        .or_else(|_| DateTime::parse_from_rfc3339(published))
+        .map(|date| date.format(PUBLISHED_FORMAT).to_string())
+        .unwrap_or_else(|_| published.to_string())
+}
+
+// 去掉HTML标签并截断
+fn to_summary(html: &str) -> String {
+    let text = Html::parse_fragment(html)
+        .root_element()
+        .text()
+        .collect::<String>();
+    let text = normalize_text(&text);
+    match text.char_indices().nth(SUMMARY_LENGTH) {
+        Some((index, _)) => format!("{}…", &text[..index]),
+        None => text,
+    }
+}
@@ -0,0 +1,475 @@
+use crate::{
+    AppWindow, NewsData, NewsFeed, NewsItem,
+    logic::{
+        APP_PATH,
+        config::{NewsFeedConfig, get_config, update_config},
+        network::{NetworkError, get_text},
+        storage::{hash_bytes, load_json, save_json},
+    },
+};
+use chrono::{Local, NaiveDateTime};
+use feed::{FeedItem, parse_feed};
+use serde::{Deserialize, Serialize};
+use slint::{ComponentHandle, Model, VecModel, Weak, invoke_from_event_loop};
+use std::{
+    cell::RefCell,
+    collections::{BTreeMap, BTreeSet, HashMap},
+    path::PathBuf,
+    rc::Rc,
+};
+
+mod feed;
+#[cfg(test)]
+mod tests;
+
+// 不强制刷新时，超过这个时间的缓存才重新获取
+const FEED_TTL_MINUTES: i64 = 30;
+// fetched_at的格式
+const FETCHED_AT_FORMAT: &str = "%Y-%m-%d %H:%M:%S";
+
+thread_local! {
+    static NEWS: RefCell<NewsState> = RefCell::new(NewsState::load());
+}
+
+// 订阅源的缓存，保存到data/news/<地址的哈希>.json
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct FeedCache {
+    pub fetched_at: String,
+    pub title: String,
+    pub items: Vec<FeedItem>,
+}
+
+impl FeedCache {
+    fn new(xml: &str) -> Result<Self, NewsError> {
+        let feed = parse_feed(xml).map_err(NewsError::Parse)?;
+        Ok(FeedCache {
+            fetched_at: Local::now().format(FETCHED_AT_FORMAT).to_string(),
+            title: feed.title,
+            items: feed.items,
+        })
+    }
+
+    // 获取时间未知的缓存按过期处理
+    fn is_stale(&self, now: NaiveDateTime) -> bool {
+        match NaiveDateTime::parse_from_str(&self.fetched_at, FETCHED_AT_FORMAT) {
+            Ok(fetched_at) => now - fetched_at >= chrono::Duration::minutes(FEED_TTL_MINUTES),
+            Err(_) => true,
+        }
+    }
+}
+
+// 已读的新闻，订阅源地址 -> 新闻id，保存到data/news/read.json
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(transparent)]
+pub struct ReadState(BTreeMap<String, BTreeSet<String>>);
+
+impl ReadState {
+    fn is_read(&self, url: &str, id: &str) -> bool {
+        self.0.get(url).is_some_and(|ids| ids.contains(id))
+    }
+
+    fn set_read(&mut self, url: &str, id: &str, read: bool) {
+        if read {
+            self.0
+                .entry(url.to_string())
+                .or_default()
+                .insert(id.to_string());
+        } else if let Some(ids) = self.0.get_mut(url) {
+            ids.remove(id);
+        }
+    }
+
+    // 订阅源中已经没有的新闻不再记录
+    fn prune(&mut self, url: &str, items: &[FeedItem]) {
+        if let Some(ids) = self.0.get_mut(url) {
+            ids.retain(|id| items.iter().any(|item| item.id == *id));
+            if ids.is_empty() {
+                self.0.remove(url);
+            }
+        }
+    }
+
+    fn remove_feed(&mut self, url: &str) {
+        self.0.remove(url);
+    }
+}
+
+// 界面线程上的新闻数据，errors为最近一次获取失败的原因
+// pending为获取进行中时新请求的订阅源，当前获取完成后接着获取
+#[derive(Default)]
+struct NewsState {
+    caches: HashMap<String, FeedCache>,
+    errors: HashMap<String, String>,
+    read: ReadState,
+    is_fetching: bool,
+    pending: Vec<String>,
+}
+
+impl NewsState {
+    fn load() -> Self {
+        let caches = get_config()
+            .news_feeds
+            .into_iter()
+            .filter_map(|feed| {
+                let cache = load_json::<FeedCache>(&get_cache_path(&feed.url))?;
+                Some((feed.url, cache))
+            })
+            .collect();
+        NewsState {
+            caches,
+            read: load_json(&get_read_path()).unwrap_or_default(),
+            ..Default::default()
+        }
+    }
+
+    fn save_read(&self) {
+        if let Err(err) = create_news_dir().and_then(|_| save_json(&get_read_path(), &self.read)) {
+            eprintln!("新闻已读状态保存失败：{}", err);
+        }
+    }
+
+    fn to_news_feed(&self, feed: &NewsFeedConfig) -> NewsFeed {
+        let cache = self.caches.get(&feed.url);
+        let items = cache
+            .map(|cache| {
+                cache
+                    .items
+                    .iter()
+                    .map(|item| NewsItem {
+                        id: item.id.clone().into(),
+                        title: item.title.clone().into(),
+                        link: item.link.clone().into(),
+                        published: item.published.clone().into(),
+                        summary: item.summary.clone().into(),
+                        read: self.read.is_read(&feed.url, &item.id),
+                    })
+                    .collect::<Vec<NewsItem>>()
+            })
+            .unwrap_or_default();
+        let name = match cache {
+            _ if !feed.name.is_empty() => feed.name.clone(),
+            Some(cache) if !cache.title.is_empty() => cache.title.clone(),
+            _ => feed.url.clone(),
+        };
+        NewsFeed {
+            name: name.into(),
+            url: feed.url.clone().into(),
+            unread: items.iter().filter(|item| !item.read).count() as i32,
+            fetched_at: cache
+                .map(|cache| cache.fetched_at.clone())
+                .unwrap_or_default()
+                .into(),
+            error: self
+                .errors
+                .get(&feed.url)
+                .cloned()
+                .unwrap_or_default()
+                .into(),
+            items: Rc::new(VecModel::from(items)).into(),
+        }
+    }
+}
+
+// 获取订阅源过程中可能出现的错误
+#[derive(Debug)]
+pub enum NewsError {
+    Network(NetworkError),
+    // 内容不是RSS或Atom
+    Parse(String),
+}
+
+impl std::fmt::Display for NewsError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            NewsError::Network(err) => write!(f, "{}", err),
+            NewsError::Parse(msg) => write!(f, "订阅源解析失败：{}", msg),
+        }
+    }
+}
+
+impl std::error::Error for NewsError {}
+
+impl From<NetworkError> for NewsError {
+    fn from(err: NetworkError) -> Self {
+        NewsError::Network(err)
+    }
+}
+
+fn get_news_dir() -> PathBuf {
+    APP_PATH.join("data").join("news")
+}
+
+fn create_news_dir() -> std::io::Result<()> {
+    std::fs::create_dir_all(get_news_dir())
+}
+
+fn get_cache_path(url: &str) -> PathBuf {
+    get_news_dir().join(format!("{}.json", hash_bytes(url.as_bytes())))
+}
+
+fn get_read_path() -> PathBuf {
+    get_news_dir().join("read.json")
+}
+
+// 在后台线程调用
+fn fetch_feed(url: &str) -> Result<FeedCache, NewsError> {
+    let cache = FeedCache::new(&get_text(url)?)?;
+    if let Err(err) = create_news_dir().and_then(|_| save_json(&get_cache_path(url), &cache)) {
+        eprintln!("{}的缓存保存失败：{}", url, err);
+    }
+    Ok(cache)
+}
+
+pub fn set_news_logic(app: Weak<AppWindow>) {
+    let app = app.unwrap();
+    let news_data = app.global::<NewsData>();
+    let weak = app.as_weak();
+    news_data.on_refresh_news(move |force| refresh_news(weak.clone(), force));
+    let weak = app.as_weak();
+    news_data.on_add_feed(move |name, url| add_feed(weak.clone(), &name, &url));
+    let weak = app.as_weak();
+    news_data.on_remove_feed(move |url| remove_feed(&weak.unwrap(), &url));
+    let weak = app.as_weak();
+    news_data.on_set_read(move |url, id, read| {
+        NEWS.with(|news| {
+            let mut news = news.borrow_mut();
+            news.read.set_read(&url, &id, read);
+            news.save_read();
+        });
+        update_read_state(&weak.unwrap(), &url);
+    });
+    let weak = app.as_weak();
+    news_data.on_mark_all_read(move |url| {
+        NEWS.with(|news| {
+            let mut news = news.borrow_mut();
+            let ids = news
+                .caches
+                .get(url.as_str())
+                .map(|cache| cache.items.iter().map(|item| item.id.clone()).collect())
+                .unwrap_or_else(Vec::new);
+            for id in ids {
+                news.read.set_read(&url, &id, true);
+            }
+            news.save_read();
+        });
+        update_read_state(&weak.unwrap(), &url);
+    });
+    let weak = app.as_weak();
+    news_data.on_open_item(move |url, item| {
+        if let Err(err) = open_link(&item.link) {
+            eprintln!("{}打开失败：{}", item.link, err);
+        }
+        NEWS.with(|news| {
+            let mut news = news.borrow_mut();
+            news.read.set_read(&url, &item.id, true);
+            news.save_read();
+        });
+        update_read_state(&weak.unwrap(), &url);
+    });
+}
+
+// 启动时只显示缓存，打开新闻页面时再获取
+pub fn init_news(app: Weak<AppWindow>) {
+    refresh_news_model(&app.unwrap());
+}
+
+// 重新生成整个订阅源列表，订阅源增减或获取完成后调用
+fn refresh_news_model(app: &AppWindow) {
+    let feeds = get_config().news_feeds;
+    let news_feeds = NEWS.with(|news| {
+        let news = news.borrow();
+        feeds
+            .iter()
+            .map(|feed| news.to_news_feed(feed))
+            .collect::<Vec<NewsFeed>>()
+    });
+    let news_data = app.global::<NewsData>();
+    let current_feed = news_data.get_current_feed();
+    if current_feed >= news_feeds.len() as i32 {
+        news_data.set_current_feed((news_feeds.len() as i32 - 1).max(0));
+    }
+    news_data.set_feeds(Rc::new(VecModel::from(news_feeds)).into());
+}
+
+// 只修改已读状态，不替换新闻列表，列表的滚动位置保持不变
+fn update_read_state(app: &AppWindow, url: &str) {
+    let feeds = app.global::<NewsData>().get_feeds();
+    let Some((index, mut feed)) = feeds.iter().enumerate().find(|(_, feed)| feed.url == url) else {
+        return;
+    };
+    let mut unread = 0;
+    NEWS.with(|news| {
+        let news = news.borrow();
+        for (i, mut item) in feed.items.iter().enumerate() {
+            let read = news.read.is_read(url, &item.id);
+            unread += !read as i32;
+            if item.read != read {
+                item.read = read;
+                feed.items.set_row_data(i, item);
+            }
+        }
+    });
+    feed.unread = unread;
+    feeds.set_row_data(index, feed);
+}
+
+// 在后台线程获取过期的订阅源，force为true时获取全部订阅源
+pub fn refresh_news(app_weak: Weak<AppWindow>, force: bool) {
+    let now = Local::now().naive_local();
+    let urls = NEWS.with(|news| {
+        let news = news.borrow();
+        get_config()
+            .news_feeds
+            .into_iter()
+            .filter(|feed| {
+                force
+                    || news
+                        .caches
+                        .get(&feed.url)
+                        .is_none_or(|cache| cache.is_stale(now))
+            })
+            .map(|feed| feed.url)
+            .collect::<Vec<String>>()
+    });
+    fetch_feeds(app_weak, urls);
+}
+
+fn fetch_feeds(app_weak: Weak<AppWindow>, urls: Vec<String>) {
+    if urls.is_empty() {
+        return;
+    }
+    let queued = NEWS.with(|news| {
+        let mut news = news.borrow_mut();
+        if !news.is_fetching {
+            news.is_fetching = true;
+            return false;
+        }
+        for url in urls.iter() {
+            if !news.pending.contains(url) {
+                news.pending.push(url.clone());
+            }
+        }
+        true
+    });
+    if queued {
+        return;
+    }
+    let app = app_weak.unwrap();
+    app.global::<NewsData>().set_is_loading(true);
+    std::thread::spawn(move || {
+        let results = urls
+            .into_iter()
+            .map(|url| {
+                let result = fetch_feed(&url).map_err(|err| err.to_string());
+                (url, result)
+            })
+            .collect::<Vec<(String, Result<FeedCache, String>)>>();
+        invoke_from_event_loop(move || {
+            NEWS.with(|news| {
+                let mut news = news.borrow_mut();
+                for (url, result) in results {
+                    match result {
+                        Ok(cache) => {
+                            news.read.prune(&url, &cache.items);
+                            news.errors.remove(&url);
+                            news.caches.insert(url, cache);
+                        }
+                        Err(err) => {
+                            eprintln!("{}获取失败：{}", url, err);
+                            news.errors.insert(url, err);
+                        }
+                    }
+                }
+                news.is_fetching = false;
+                news.save_read();
+            });
+            let app = app_weak.unwrap();
+            refresh_news_model(&app);
+            app.global::<NewsData>().set_is_loading(false);
+            // 排队期间被删除的订阅源不再获取
+            let feeds = get_config().news_feeds;
+            let pending = NEWS
+                .with(|news| std::mem::take(&mut news.borrow_mut().pending))
+                .into_iter()
+                .filter(|url| feeds.iter().any(|feed| &feed.url == url))
+                .collect::<Vec<String>>();
+            fetch_feeds(app_weak, pending);
+        })
+        .unwrap();
+    });
+}
+
+// 已经存在的订阅源不重复添加，添加后立即获取
+fn add_feed(app_weak: Weak<AppWindow>, name: &str, url: &str) {
+    let url = url.trim().to_string();
+    let app = app_weak.unwrap();
+    let news_data = app.global::<NewsData>();
+    if !is_http_url(&url) {
+        news_data.set_message("订阅源地址需要以http://或https://开头".into());
+        return;
+    }
+    if get_config().news_feeds.iter().any(|feed| feed.url == url) {
+        news_data.set_message("这个订阅源已经添加过了".into());
+        return;
+    }
+    news_data.set_message("".into());
+    update_config(|config| {
+        config.news_feeds.push(NewsFeedConfig {
+            name: name.trim().to_string(),
+            url: url.clone(),
+        })
+    });
+    refresh_news_model(&app);
+    news_data.set_current_feed(news_data.get_feeds().row_count() as i32 - 1);
+    fetch_feeds(app_weak, vec![url]);
+}
+
+// 同时删除缓存和已读记录
+fn remove_feed(app: &AppWindow, url: &str) {
+    update_config(|config| config.news_feeds.retain(|feed| feed.url != url));
+    let cache_path = get_cache_path(url);
+    if cache_path.exists()
+        && let Err(err) = std::fs::remove_file(&cache_path)
+    {
+        eprintln!("{}删除失败：{}", cache_path.display(), err);
+    }
+    NEWS.with(|news| {
+        let mut news = news.borrow_mut();
+        news.caches.remove(url);
+        news.errors.remove(url);
+        news.read.remove_feed(url);
+        news.save_read();
+    });
+    refresh_news_model(app);
+}
+
+fn is_http_url(url: &str) -> bool {
+    url.starts_with("http://") || url.starts_with("https://")
+}
+
+// 用系统默认的浏览器打开，链接来自订阅源，只打开网页链接，不执行file:等其他协议
+fn open_link(link: &str) -> std::io::Result<()> {
+    if link.is_empty() {
+        return Err(std::io::Error::new(
+            std::io::ErrorKind::InvalidInput,
+            "新闻没有链接",
+        ));
+    }
+    if !is_http_url(link) {
+        return Err(std::io::Error::new(
+            std::io::ErrorKind::InvalidInput,
+            "只能打开http://或https://链接",
+        ));
+    }
+    // cmd的start会把链接中的&当作命令分隔符，Windows下改用explorer
+    let program = if cfg!(windows) {
+        "explorer"
+    } else if cfg!(target_os = "macos") {
+        "open"
+    } else {
+        "xdg-open"
+    };
+    std::process::Command::new(program).arg(link).spawn()?;
+    Ok(())
+}
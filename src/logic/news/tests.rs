@@ -0,0 +1,160 @@
+use crate::logic::news::{
+    FeedCache, ReadState,
+    feed::{FeedItem, parse_feed},
+    open_link,
+};
+use chrono::NaiveDate;
+
+// 手写的订阅源，覆盖RSS 2.0、Atom和RSS 1.0的常见写法
+const FIXTURE_RSS: &str = include_str!(concat!(
+    env!("CARGO_MANIFEST_DIR"),
+    "/tests/fixtures/news/rss.xml"
+));
+const FIXTURE_ATOM: &str = include_str!(concat!(
+    env!("CARGO_MANIFEST_DIR"),
+    "/tests/fixtures/news/atom.xml"
+));
+const FIXTURE_RDF: &str = include_str!(concat!(
+    env!("CARGO_MANIFEST_DIR"),
+    "/tests/fixtures/news/rdf.xml"
+));
+const FIXTURE_RSS091: &str = include_str!(concat!(
+    env!("CARGO_MANIFEST_DIR"),
+    "/tests/fixtures/news/rss091.xml"
+));
+
+#[test]
+fn parse_rss() {
+    let feed = parse_feed(FIXTURE_RSS).unwrap();
+    assert_eq!(feed.title, "示例新闻");
+    assert_eq!(
+        feed.items,
+        vec![
+            FeedItem {
+                id: "news-1".to_string(),
+                title: "第一条新闻".to_string(),
+                link: "https://news.example.com/articles/1".to_string(),
+                published: "2025-06-10 08:30".to_string(),
+                summary: "这是第一条新闻的摘要。 第二段".to_string(),
+            },
+            FeedItem {
+                id: "https://news.example.com/articles/2".to_string(),
+                title: "没有guid的新闻".to_string(),
+                link: "https://news.example.com/articles/2".to_string(),
+                published: "2025-06-09 20:00".to_string(),
+                summary: "纯文本摘要 & 转义字符".to_string(),
+            },
+            FeedItem {
+                id: "日期格式不标准的新闻".to_string(),
+                title: "日期格式不标准的新闻".to_string(),
+                link: String::new(),
+                published: "2025年6月8日".to_string(),
+                summary: String::new(),
+            },
+        ]
+    );
+}
+
+#[test]
+fn parse_atom() {
+    let feed = parse_feed(FIXTURE_ATOM).unwrap();
+    assert_eq!(feed.title, "Example Blog");
+    assert_eq!(
+        feed.items,
+        vec![
+            FeedItem {
+                id: "tag:blog.example.com,2025:hello".to_string(),
+                title: "Hello & welcome".to_string(),
+                link: "https://blog.example.com/posts/hello".to_string(),
+                published: "2025-06-10 12:00".to_string(),
+                summary: "First post with a link.".to_string(),
+            },
+            FeedItem {
+                id: "tag:blog.example.com,2025:updated".to_string(),
+                title: "Updated only".to_string(),
+                link: "https://blog.example.com/posts/updated".to_string(),
+                published: "2025-06-09 00:00".to_string(),
+                summary: "Short summary".to_string(),
+            },
+        ]
+    );
+}
+
+#[test]
+fn parse_rdf() {
+    let feed = parse_feed(FIXTURE_RDF).unwrap();
+    assert_eq!(feed.title, "RSS 1.0 Feed");
+    assert_eq!(
+        feed.items,
+        vec![FeedItem {
+            id: "https://rdf.example.com/1".to_string(),
+            title: "RDF item".to_string(),
+            link: "https://rdf.example.com/1".to_string(),
+            published: "2025-06-07 09:15".to_string(),
+            summary: "RDF description".to_string(),
+        }]
+    );
+}
+
+#[test]
+fn parse_invalid_feed() {
+    assert!(parse_feed("<html><body></body></html>").is_err());
+    assert!(parse_feed("not xml").is_err());
+}
+
+#[test]
+fn parse_feed_with_doctype() {
+    let feed = parse_feed(FIXTURE_RSS091).unwrap();
+    assert_eq!(feed.title, "旧式订阅源");
+    assert_eq!(feed.items.len(), 1);
+    assert_eq!(feed.items[0].title, "带有DOCTYPE的新闻");
+}
+
+#[test]
+fn long_summary_is_truncated() {
+    let xml = format!(
+        "<rss><channel><title>t</title><item><title>a</title><description>{}</description></item></channel></rss>",
+        "长".repeat(300)
+    );
+    let feed = parse_feed(&xml).unwrap();
+    assert_eq!(feed.items[0].summary, format!("{}…", "长".repeat(200)));
+}
+
+#[test]
+fn read_state_is_pruned() {
+    let feed = parse_feed(FIXTURE_RSS).unwrap();
+    let url = "https://news.example.com/rss.xml";
+    let mut read = ReadState::default();
+    read.set_read(url, "news-1", true);
+    read.set_read(url, "removed", true);
+    read.prune(url, &feed.items);
+    assert!(read.is_read(url, "news-1"));
+    assert!(!read.is_read(url, "removed"));
+    read.set_read(url, "news-1", false);
+    read.prune(url, &feed.items);
+    assert!(read.0.is_empty());
+}
+
+#[test]
+fn cache_expires() {
+    let cache = FeedCache {
+        fetched_at: "2025-06-10 08:00:00".to_string(),
+        ..Default::default()
+    };
+    let at = |hour, min| {
+        NaiveDate::from_ymd_opt(2025, 6, 10)
+            .unwrap()
+            .and_hms_opt(hour, min, 0)
+            .unwrap()
+    };
+    assert!(!cache.is_stale(at(8, 29)));
+    assert!(cache.is_stale(at(8, 30)));
+    assert!(FeedCache::default().is_stale(at(8, 0)));
+}
+
+#[test]
+fn open_only_web_links() {
+    for link in ["", "file:///etc/passwd", "javascript:alert(1)", "calc.exe"] {
+        assert!(open_link(link).is_err(), "{}", link);
+    }
+}
@@ -0,0 +1,200 @@
+use crate::{
+    SlintWeekday, Todo, TodoKind,
+    logic::{
+        SlintDate,
+        storage::{Migration, load_versioned},
+    },
+};
+use serde::{Deserialize, Serialize};
+use serde_json::{Value, json};
+
+#[cfg(test)]
+mod tests;
+
+// 待办数据文件的当前版本，修改TodoRecord的结构时需要加一并在MIGRATIONS末尾追加迁移函数
+pub const TODO_FILE_VERSION: u32 = 2;
+// MIGRATIONS[i]把第i版的数据迁移到第i+1版
+const MIGRATIONS: [Migration; TODO_FILE_VERSION as usize] = [migrate_v0_to_v1, migrate_v1_to_v2];
+
+// 保存到todo_list.json的数据，带有版本号，加载旧版本数据时依次执行迁移
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(try_from = "Value")]
+pub struct TodoFile {
+    version: u32,
+    pub todos: Vec<TodoRecord>,
+}
+
+// 待办在磁盘上的格式，与界面上的Todo解耦，只保存无法推算出来的字段
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct TodoRecord {
+    pub id: String,
+    pub text: String,
+    pub created_at: String,
+    pub kind: KindRecord,
+    pub week: WeekdayRecord,
+    pub day: i32,
+    pub once: SlintDate,
+    pub current_progress: i32,
+    pub max_progress: i32,
+    pub start_date: SlintDate,
+    pub end_date: SlintDate,
+    pub note: String,
+    // 提醒时间，格式为HH:MM，为空表示不提醒
+    pub remind_time: String,
+}
+
+#[derive(Debug, Clone, Copy, Default, Serialize, Deserialize)]
+pub enum KindRecord {
+    #[default]
+    Once,
+    Daily,
+    Weekly,
+    Monthly,
+    Progress,
+}
+
+#[derive(Debug, Clone, Copy, Default, Serialize, Deserialize)]
+pub enum WeekdayRecord {
+    #[default]
+    Monday,
+    Tuesday,
+    Wednesday,
+    Thursday,
+    Friday,
+    Saturday,
+    Sunday,
+}
+
+impl TodoFile {
+    pub fn new(todos: Vec<TodoRecord>) -> Self {
+        TodoFile {
+            version: TODO_FILE_VERSION,
+            todos,
+        }
+    }
+}
+
+impl TryFrom<Value> for TodoFile {
+    type Error = String;
+
+    // 第0版直接保存的是界面上的Todo数组，没有版本号
+    fn try_from(value: Value) -> Result<Self, Self::Error> {
+        let mut value = load_versioned(value, &MIGRATIONS, "待办数据")?;
+        let todos = value.get_mut("todos").map(Value::take).unwrap_or_default();
+        let todos = serde_json::from_value(todos).map_err(|e| e.to_string())?;
+        Ok(TodoFile::new(todos))
+    }
+}
+
+// 第0版的字段名与TodoRecord一致，多余的推算字段在反序列化时忽略
+fn migrate_v0_to_v1(value: Value) -> Result<Value, String> {
+    Ok(json!({ "version": 1, "todos": value }))
+}
+
+// 第1版没有提醒时间，迁移后的待办都不提醒
+fn migrate_v1_to_v2(mut value: Value) -> Result<Value, String> {
+    let map = value.as_object_mut().ok_or("待办数据格式错误")?;
+    map.insert("version".to_string(), json!(2));
+    if let Some(Value::Array(todos)) = map.get_mut("todos") {
+        for todo in todos.iter_mut().filter_map(Value::as_object_mut) {
+            todo.insert("remind_time".to_string(), json!(""));
+        }
+    }
+    Ok(value)
+}
+
+impl From<&Todo> for TodoRecord {
+    fn from(todo: &Todo) -> Self {
+        TodoRecord {
+            id: todo.id.to_string(),
+            text: todo.text.to_string(),
+            created_at: todo.created_at.to_string(),
+            kind: todo.kind.into(),
+            week: todo.week.into(),
+            day: todo.day,
+            once: todo.once.clone(),
+            current_progress: todo.current_progress,
+            max_progress: todo.max_progress,
+            start_date: todo.start_date.clone(),
+            end_date: todo.end_date.clone(),
+            note: todo.note.to_string(),
+            remind_time: todo.remind_time.to_string(),
+        }
+    }
+}
+
+impl From<TodoRecord> for Todo {
+    fn from(record: TodoRecord) -> Self {
+        let kind: TodoKind = record.kind.into();
+        Todo {
+            id: record.id.into(),
+            text: record.text.into(),
+            created_at: record.created_at.into(),
+            recurrence: matches!(kind, TodoKind::Daily | TodoKind::Weekly | TodoKind::Monthly),
+            kind,
+            week: record.week.into(),
+            day: record.day,
+            once: record.once,
+            current_progress: record.current_progress,
+            max_progress: record.max_progress,
+            start_date: record.start_date,
+            end_date: record.end_date,
+            note: record.note.into(),
+            remind_time: record.remind_time.into(),
+            ..Default::default()
+        }
+    }
+}
+
+impl From<TodoKind> for KindRecord {
+    fn from(kind: TodoKind) -> Self {
+        match kind {
+            TodoKind::Once => KindRecord::Once,
+            TodoKind::Daily => KindRecord::Daily,
+            TodoKind::Weekly => KindRecord::Weekly,
+            TodoKind::Monthly => KindRecord::Monthly,
+            TodoKind::Progress => KindRecord::Progress,
+        }
+    }
+}
+
+impl From<KindRecord> for TodoKind {
+    fn from(kind: KindRecord) -> Self {
+        match kind {
+            KindRecord::Once => TodoKind::Once,
+            KindRecord::Daily => TodoKind::Daily,
+            KindRecord::Weekly => TodoKind::Weekly,
+            KindRecord::Monthly => TodoKind::Monthly,
+            KindRecord::Progress => TodoKind::Progress,
+        }
+    }
+}
+
+impl From<SlintWeekday> for WeekdayRecord {
+    fn from(weekday: SlintWeekday) -> Self {
+        match weekday {
+            SlintWeekday::Monday => WeekdayRecord::Monday,
+            SlintWeekday::Tuesday => WeekdayRecord::Tuesday,
+            SlintWeekday::Wednesday => WeekdayRecord::Wednesday,
+            SlintWeekday::Thursday => WeekdayRecord::Thursday,
+            SlintWeekday::Friday => WeekdayRecord::Friday,
+            SlintWeekday::Saturday => WeekdayRecord::Saturday,
+            SlintWeekday::Sunday => WeekdayRecord::Sunday,
+        }
+    }
+}
+
+impl From<WeekdayRecord> for SlintWeekday {
+    fn from(weekday: WeekdayRecord) -> Self {
+        match weekday {
+            WeekdayRecord::Monday => SlintWeekday::Monday,
+            WeekdayRecord::Tuesday => SlintWeekday::Tuesday,
+            WeekdayRecord::Wednesday => SlintWeekday::Wednesday,
+            WeekdayRecord::Thursday => SlintWeekday::Thursday,
+            WeekdayRecord::Friday => SlintWeekday::Friday,
+            WeekdayRecord::Saturday => SlintWeekday::Saturday,
+            WeekdayRecord::Sunday => SlintWeekday::Sunday,
+        }
+    }
+}
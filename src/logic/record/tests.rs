@@ -0,0 +1,75 @@
+use crate::{
+    SlintWeekday, Todo, TodoKind,
+    logic::{
+        SlintDate,
+        record::{KindRecord, TODO_FILE_VERSION, TodoFile},
+    },
+};
+use serde_json::{Value, json};
+
+// 第0版直接保存的界面上的Todo数组，id是秒级时间戳，包含推算出来的字段
+const FIXTURE_V0: &str = include_str!(concat!(
+    env!("CARGO_MANIFEST_DIR"),
+    "/tests/fixtures/todo/todo_list_v0.json"
+));
+
+#[test]
+fn migrate_todo_file_v0() {
+    let file = serde_json::from_str::<TodoFile>(FIXTURE_V0).unwrap();
+    assert_eq!(file.todos.len(), 3);
+    let weekly = &file.todos[0];
+    assert_eq!(weekly.id, "1700000000");
+    assert_eq!(weekly.text, "写周报");
+    assert_eq!(weekly.created_at, "2023-11-15");
+    assert!(matches!(weekly.kind, KindRecord::Weekly));
+    assert_eq!(weekly.note, "周五下班前");
+    assert_eq!(weekly.remind_time, "");
+    let todo = Todo::from(weekly.clone());
+    assert_eq!(todo.kind, TodoKind::Weekly);
+    assert_eq!(todo.week, SlintWeekday::Friday);
+    assert!(todo.recurrence);
+    assert_eq!(
+        todo.end_date,
+        SlintDate {
+            year: 2024,
+            month: 11,
+            day: 15
+        }
+    );
+    let progress = Todo::from(file.todos[1].clone());
+    assert_eq!(progress.kind, TodoKind::Progress);
+    assert_eq!(
+        (progress.current_progress, progress.max_progress),
+        (120, 300)
+    );
+    let once = Todo::from(file.todos[2].clone());
+    assert_eq!(once.kind, TodoKind::Once);
+    assert_eq!(once.once.day, 20);
+    // 推算出来的字段不保存，加载后重新计算
+    assert!(!once.is_expired);
+}
+
+#[test]
+fn migrate_todo_file_v1() {
+    let value = json!({
+        "version": 1,
+        "todos": [{ "id": "1", "text": "旧待办", "kind": "Daily" }]
+    });
+    let file = serde_json::from_value::<TodoFile>(value).unwrap();
+    assert_eq!(file.todos[0].text, "旧待办");
+    assert_eq!(file.todos[0].remind_time, "");
+}
+
+#[test]
+fn saved_file_has_current_version() {
+    let value = serde_json::to_value(TodoFile::new(vec![])).unwrap();
+    assert_eq!(value["version"], json!(TODO_FILE_VERSION));
+    assert!(serde_json::from_value::<TodoFile>(value).is_ok());
+}
+
+#[test]
+fn reject_newer_version() {
+    let value = json!({ "version": TODO_FILE_VERSION + 1, "todos": [] });
+    assert!(serde_json::from_value::<TodoFile>(value).is_err());
+    assert!(serde_json::from_value::<TodoFile>(Value::Null).is_err());
+}
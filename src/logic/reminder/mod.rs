@@ -0,0 +1,329 @@
+use crate::{
+    AppWindow, Reminder, ReminderData, Todo, TodoKind,
+    logic::{
+        APP_PATH,
+        config::get_config,
+        storage::{load_json, save_json_without_backup},
+        todo::TODOS_MODEL,
+    },
+};
+use chrono::{Days, Duration, Local, NaiveDate, NaiveDateTime, NaiveTime};
+use notify_rust::Notification;
+use serde::{Deserialize, Serialize};
+use slint::{ComponentHandle, Timer, TimerMode, VecModel, Weak, invoke_from_event_loop};
+use std::{cell::RefCell, path::PathBuf, rc::Rc};
+
+#[cfg(test)]
+mod tests;
+
+// 检查提醒的间隔
+const CHECK_INTERVAL: std::time::Duration = std::time::Duration::from_secs(30);
+// 到期超过这个时间才检查到的提醒不再弹出通知，放到错过的提醒中
+const MISSED_GRACE_MINUTES: i64 = 5;
+// 程序关闭期间错过的提醒最多往前找几天
+const MISSED_LOOKBACK_DAYS: u64 = 7;
+// 待办提醒时间的格式
+pub const REMIND_TIME_FORMAT: &str = "%H:%M";
+const CHECKED_AT_FORMAT: &str = "%Y-%m-%d %H:%M:%S";
+const DUE_FORMAT: &str = "%Y-%m-%d %H:%M";
+
+thread_local! {
+    static REMINDERS: RefCell<ReminderState> = RefCell::new(ReminderState::load());
+    // 定时器被释放后就不再触发，所以和状态一起保存在界面线程上
+    static TIMER: Timer = Timer::default();
+}
+
+// 设置了提醒时间的待办和它的完成记录
+pub struct ReminderEntry {
+    pub todo: Todo,
+    pub done: Vec<NaiveDate>,
+}
+
+// 待办在某一天需要提醒的时间
+#[derive(Debug, Clone, PartialEq)]
+pub struct Occurrence {
+    pub todo_id: String,
+    pub text: String,
+    pub due: NaiveDateTime,
+}
+
+// 检查到的提醒，lead为提前的分钟数，0表示已经到时间
+#[derive(Debug, Clone, PartialEq)]
+pub enum ReminderEvent {
+    Notify(Occurrence, i64),
+    Missed(Occurrence),
+}
+
+// 错过的提醒，key由待办id和日期组成，同一次待办只记录一条
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct MissedReminder {
+    pub key: String,
+    pub todo_id: String,
+    pub text: String,
+    pub due: String,
+}
+
+// 保存到data/reminders.json，checked_at为上一次检查的时间，程序关闭期间的提醒按错过处理
+#[derive(Debug, Default, Serialize, Deserialize)]
+#[serde(default)]
+struct ReminderState {
+    checked_at: String,
+    missed: Vec<MissedReminder>,
+}
+
+impl ReminderEntry {
+    // from到to之间（包括两端）每次需要提醒的时间，已完成的不提醒
+    pub fn get_occurrences(&self, from: NaiveDate, to: NaiveDate) -> Vec<Occurrence> {
+        let todo = &self.todo;
+        let Some(time) = parse_remind_time(&todo.remind_time) else {
+            return vec![];
+        };
+        // 单次和进度待办完成一次后就不再提醒
+        if matches!(todo.kind, TodoKind::Once | TodoKind::Progress) && !self.done.is_empty() {
+            return vec![];
+        }
+        from.iter_days()
+            .take_while(|date| *date <= to)
+            .filter(|date| todo.occurs_on(*date) && !self.done.contains(date))
+            .map(|date| Occurrence {
+                todo_id: todo.id.to_string(),
+                text: todo.text.to_string(),
+                due: date.and_time(time),
+            })
+            .collect()
+    }
+}
+
+impl Occurrence {
+    fn get_key(&self) -> String {
+        format!("{}|{}", self.todo_id, self.due.date())
+    }
+
+    // 在(last, now]之间到了提醒时间时，同一次待办只提醒最近的一个时间
+    pub fn check(
+        &self,
+        leads: &[i64],
+        last: NaiveDateTime,
+        now: NaiveDateTime,
+    ) -> Option<ReminderEvent> {
+        let lead = std::iter::once(0)
+            .chain(leads.iter().copied())
+            .filter(|lead| {
+                let remind_at = self.due - Duration::minutes(*lead);
+                last < remind_at && remind_at <= now
+            })
+            .min()?;
+        if now - self.due > Duration::minutes(MISSED_GRACE_MINUTES) {
+            Some(ReminderEvent::Missed(self.clone()))
+        } else {
+            Some(ReminderEvent::Notify(self.clone(), lead))
+        }
+    }
+
+    fn to_missed(&self) -> MissedReminder {
+        MissedReminder {
+            key: self.get_key(),
+            todo_id: self.todo_id.clone(),
+            text: self.text.clone(),
+            due: self.due.format(DUE_FORMAT).to_string(),
+        }
+    }
+}
+
+impl ReminderState {
+    fn load() -> Self {
+        load_json(&get_reminder_path()).unwrap_or_default()
+    }
+
+    // 提醒记录只影响错过的提醒列表，不需要备份
+    fn save(&self) {
+        if let Err(err) = save_json_without_backup(&get_reminder_path(), self) {
+            eprintln!("保存提醒记录失败：{}", err);
+        }
+    }
+
+    // 第一次运行或者系统时间被调回时从现在开始检查
+    fn get_checked_at(&self, now: NaiveDateTime) -> NaiveDateTime {
+        NaiveDateTime::parse_from_str(&self.checked_at, CHECKED_AT_FORMAT)
+            .ok()
+            .filter(|checked_at| *checked_at <= now)
+            .unwrap_or(now)
+    }
+
+    fn add_missed(&mut self, missed: MissedReminder) {
+        if !self.missed.iter().any(|m| m.key == missed.key) {
+            self.missed.push(missed);
+        }
+    }
+}
+
+fn get_reminder_path() -> PathBuf {
+    APP_PATH.join("data").join("reminders.json")
+}
+
+// 00:00到23:59，为空或格式不对时返回None
+pub fn parse_remind_time(text: &str) -> Option<NaiveTime> {
+    NaiveTime::parse_from_str(text.trim(), REMIND_TIME_FORMAT).ok()
+}
+
+// 检查所有待办在(last, now]之间的提醒
+pub fn collect_events(
+    entries: &[ReminderEntry],
+    leads: &[i64],
+    last: NaiveDateTime,
+    now: NaiveDateTime,
+) -> Vec<ReminderEvent> {
+    let max_lead = leads.iter().copied().max().unwrap_or(0).max(0);
+    let from = last
+        .date()
+        .max(now.date() - Days::new(MISSED_LOOKBACK_DAYS));
+    let to = (now + Duration::minutes(max_lead)).date();
+    entries
+        .iter()
+        .flat_map(|entry| entry.get_occurrences(from, to))
+        .filter_map(|occurrence| occurrence.check(leads, last, now))
+        .collect()
+}
+
+pub fn set_reminder_logic(app: Weak<AppWindow>) {
+    let app = app.unwrap();
+    let reminder_data = app.global::<ReminderData>();
+    let weak = app.as_weak();
+    reminder_data.on_dismiss_reminder(move |key| {
+        REMINDERS.with(|reminders| {
+            let mut reminders = reminders.borrow_mut();
+            reminders.missed.retain(|m| m.key != key.as_str());
+            reminders.save();
+        });
+        refresh_reminder_data(&weak.unwrap());
+    });
+    let weak = app.as_weak();
+    reminder_data.on_clear_reminders(move || {
+        REMINDERS.with(|reminders| {
+            let mut reminders = reminders.borrow_mut();
+            reminders.missed.clear();
+            reminders.save();
+        });
+        refresh_reminder_data(&weak.unwrap());
+    });
+    reminder_data
+        .on_remind_time_check(|text| text.is_empty() || parse_remind_time(&text).is_some());
+}
+
+// 启动时先检查一次，找出程序关闭期间错过的提醒，之后定时检查
+pub fn init_reminders(app: Weak<AppWindow>) {
+    check_reminders(&app.unwrap());
+    TIMER.with(|timer| {
+        timer.start(TimerMode::Repeated, CHECK_INTERVAL, move || {
+            check_reminders(&app.unwrap());
+        })
+    });
+}
+
+// 退出时保存最后一次检查的时间，下次启动时从这里开始找错过的提醒
+pub fn save_reminders() {
+    REMINDERS.with(|reminders| reminders.borrow().save());
+}
+
+fn check_reminders(app: &AppWindow) {
+    let now = Local::now().naive_local();
+    let config = get_config();
+    let leads = config
+        .reminder_lead_minutes
+        .iter()
+        .map(|lead| *lead as i64)
+        .collect::<Vec<i64>>();
+    let entries = TODOS_MODEL.with(|todos_model| todos_model.borrow().to_reminder_entries());
+    let events = REMINDERS.with(|reminders| {
+        let mut reminders = reminders.borrow_mut();
+        let last = reminders.get_checked_at(now);
+        reminders.checked_at = now.format(CHECKED_AT_FORMAT).to_string();
+        // 关闭提醒时只更新检查时间，重新打开后不会补发
+        let events = if config.reminder_enabled {
+            collect_events(&entries, &leads, last, now)
+        } else {
+            vec![]
+        };
+        for event in &events {
+            if let ReminderEvent::Missed(occurrence) = event {
+                reminders.add_missed(occurrence.to_missed());
+            }
+        }
+        // 检查时间只在有提醒时保存，避免每次检查都写文件，其余时候在退出时保存
+        if !events.is_empty() {
+            reminders.save();
+        }
+        events
+    });
+    for event in events {
+        if let ReminderEvent::Notify(occurrence, lead) = event {
+            send_notification(app.as_weak(), occurrence, lead);
+        }
+    }
+    refresh_reminder_data(app);
+}
+
+// D-Bus调用可能阻塞，在后台线程发送，发送失败时放到错过的提醒中
+fn send_notification(app_weak: Weak<AppWindow>, occurrence: Occurrence, lead: i64) {
+    std::thread::spawn(move || {
+        let body = if lead > 0 {
+            format!(
+                "{}后到时间（{}）",
+                format_lead(lead),
+                occurrence.due.format(REMIND_TIME_FORMAT)
+            )
+        } else {
+            format!(
+                "已经到时间了（{}）",
+                occurrence.due.format(REMIND_TIME_FORMAT)
+            )
+        };
+        let result = Notification::new()
+            .appname(env!("CARGO_PKG_NAME"))
+            .summary(&occurrence.text)
+            .body(&body)
+            .show();
+        if let Err(err) = result {
+            eprintln!("发送通知失败：{}", err);
+            invoke_from_event_loop(move || {
+                REMINDERS.with(|reminders| {
+                    let mut reminders = reminders.borrow_mut();
+                    reminders.add_missed(occurrence.to_missed());
+                    reminders.save();
+                });
+                refresh_reminder_data(&app_weak.unwrap());
+            })
+            .unwrap();
+        }
+    });
+}
+
+// 整小时显示为小时，其余显示为分钟
+fn format_lead(lead: i64) -> String {
+    if lead % 60 == 0 {
+        format!("{}小时", lead / 60)
+    } else {
+        format!("{}分钟", lead)
+    }
+}
+
+// 最近的排在前面
+fn refresh_reminder_data(app: &AppWindow) {
+    let missed = REMINDERS.with(|reminders| {
+        reminders
+            .borrow()
+            .missed
+            .iter()
+            .rev()
+            .map(|m| Reminder {
+                key: m.key.clone().into(),
+                todo_id: m.todo_id.clone().into(),
+                text: m.text.clone().into(),
+                due: m.due.clone().into(),
+            })
+            .collect::<Vec<Reminder>>()
+    });
+    app.global::<ReminderData>()
+        .set_missed_reminders(Rc::new(VecModel::from(missed)).into());
+}
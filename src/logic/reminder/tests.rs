@@ -0,0 +1,123 @@
+use crate::{
+    SlintWeekday, Todo, TodoKind,
+    logic::{
+        SlintDate,
+        reminder::{Occurrence, ReminderEntry, ReminderEvent, collect_events, parse_remind_time},
+    },
+};
+use chrono::{NaiveDate, NaiveDateTime};
+
+fn date(day: u32) -> NaiveDate {
+    NaiveDate::from_ymd_opt(2025, 6, day).unwrap()
+}
+
+fn at(day: u32, hour: u32, min: u32) -> NaiveDateTime {
+    date(day).and_hms_opt(hour, min, 0).unwrap()
+}
+
+// 2025年6月每周一9点提醒
+fn weekly_entry(done: Vec<NaiveDate>) -> ReminderEntry {
+    ReminderEntry {
+        todo: Todo {
+            id: "1".into(),
+            text: "周会".into(),
+            kind: TodoKind::Weekly,
+            week: SlintWeekday::Monday,
+            start_date: SlintDate::from_naive_date(&date(1)),
+            end_date: SlintDate::from_naive_date(&date(30)),
+            remind_time: "09:00".into(),
+            ..Default::default()
+        },
+        done,
+    }
+}
+
+fn occurrence(day: u32) -> Occurrence {
+    Occurrence {
+        todo_id: "1".to_string(),
+        text: "周会".to_string(),
+        due: at(day, 9, 0),
+    }
+}
+
+#[test]
+fn parse_time() {
+    assert!(parse_remind_time("09:30").is_some());
+    assert!(parse_remind_time(" 23:59 ").is_some());
+    assert!(parse_remind_time("").is_none());
+    assert!(parse_remind_time("24:00").is_none());
+    assert!(parse_remind_time("9点").is_none());
+}
+
+#[test]
+fn occurrences_skip_done_dates() {
+    let entry = weekly_entry(vec![date(9)]);
+    let dues = entry
+        .get_occurrences(date(1), date(20))
+        .into_iter()
+        .map(|o| o.due)
+        .collect::<Vec<NaiveDateTime>>();
+    assert_eq!(dues, vec![at(2, 9, 0), at(16, 9, 0)]);
+}
+
+#[test]
+fn once_todo_stops_after_done() {
+    let mut entry = ReminderEntry {
+        todo: Todo {
+            kind: TodoKind::Once,
+            once: SlintDate::from_naive_date(&date(10)),
+            start_date: SlintDate::from_naive_date(&date(10)),
+            end_date: SlintDate::from_naive_date(&date(10)),
+            remind_time: "18:00".into(),
+            ..Default::default()
+        },
+        done: vec![],
+    };
+    assert_eq!(entry.get_occurrences(date(1), date(30)).len(), 1);
+    entry.done.push(date(9));
+    assert!(entry.get_occurrences(date(1), date(30)).is_empty());
+}
+
+#[test]
+fn lead_and_due_notifications() {
+    let entries = [weekly_entry(vec![])];
+    let leads = [60, 10];
+    // 8:00到8:50之间到了提前60分钟的提醒
+    assert_eq!(
+        collect_events(&entries, &leads, at(2, 7, 59), at(2, 8, 0)),
+        vec![ReminderEvent::Notify(occurrence(2), 60)]
+    );
+    // 同一次检查跨过多个提醒时间时只提醒最近的一个
+    assert_eq!(
+        collect_events(&entries, &leads, at(2, 7, 0), at(2, 8, 55)),
+        vec![ReminderEvent::Notify(occurrence(2), 10)]
+    );
+    assert_eq!(
+        collect_events(&entries, &leads, at(2, 8, 59), at(2, 9, 0)),
+        vec![ReminderEvent::Notify(occurrence(2), 0)]
+    );
+    assert!(collect_events(&entries, &leads, at(2, 9, 0), at(2, 9, 30)).is_empty());
+}
+
+#[test]
+fn reminders_while_closed_are_missed() {
+    let entries = [weekly_entry(vec![])];
+    // 6月1日关闭，6月9日打开，2日和9日的提醒都错过了
+    assert_eq!(
+        collect_events(&entries, &[10], at(1, 12, 0), at(9, 12, 0)),
+        vec![
+            ReminderEvent::Missed(occurrence(2)),
+            ReminderEvent::Missed(occurrence(9)),
+        ]
+    );
+    // 最多往前找7天
+    assert_eq!(
+        collect_events(&entries, &[10], at(1, 12, 0), at(10, 12, 0)),
+        vec![ReminderEvent::Missed(occurrence(9))]
+    );
+    // 到期不久的仍然发送通知
+    assert_eq!(
+        collect_events(&entries, &[10], at(9, 8, 0), at(9, 9, 3)),
+        vec![ReminderEvent::Notify(occurrence(9), 0)]
+    );
+}
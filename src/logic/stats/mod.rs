@@ -0,0 +1,306 @@
+use crate::{
+    AppWindow, HeatmapDay, HeatmapWeek, KindStat, MonthActivity, ProgressRecord, ProgressStat,
+    StatsData, StreakStat, Todo, TodoKind,
+    logic::{
+        SlintDate,
+        archive::TODO_ARCHIVE,
+        todo::{TODOS_MODEL, get_progress_velocity, get_today},
+    },
+};
+use chrono::{Datelike, Days, Months, NaiveDate};
+use slint::{ComponentHandle, ModelRc, VecModel, Weak};
+use std::{
+    collections::{BTreeSet, HashMap},
+    rc::Rc,
+};
+
+#[cfg(test)]
+mod tests;
+
+// 热力图显示最近多少周和多少个月
+const HEATMAP_WEEKS: u64 = 16;
+const HEATMAP_MONTHS: u32 = 12;
+// 热力图颜色的等级数，0表示没有活动
+const HEATMAP_LEVELS: i32 = 4;
+const KINDS: [TodoKind; 5] = [
+    TodoKind::Once,
+    TodoKind::Daily,
+    TodoKind::Weekly,
+    TodoKind::Monthly,
+    TodoKind::Progress,
+];
+
+// 一条待办和它的完成记录，待办列表和归档中的待办都会参与统计
+pub struct StatsEntry {
+    pub todo: Todo,
+    pub done: Vec<NaiveDate>,
+    pub progress: Vec<ProgressRecord>,
+    pub archived: bool,
+}
+
+impl StatsEntry {
+    // (应该完成的次数, 实际完成的次数)，今天还没完成的不算错过
+    fn count_occurrences(&self) -> (i32, i32) {
+        let todo = &self.todo;
+        let today = get_today();
+        match todo.kind {
+            TodoKind::Once | TodoKind::Progress => {
+                let date = match todo.kind {
+                    TodoKind::Once => todo.once.to_naive_date(),
+                    _ => todo.end_date.to_naive_date(),
+                };
+                let is_done = !self.done.is_empty();
+                ((date < today || is_done) as i32, is_done as i32)
+            }
+            _ => {
+                let end = todo.end_date.to_naive_date().min(today);
+                let (mut due, mut done) = (0, 0);
+                for date in todo.start_date.to_naive_date().iter_days() {
+                    if date > end {
+                        break;
+                    }
+                    if !todo.occurs_on(date) {
+                        continue;
+                    }
+                    let is_done = self.done.contains(&date);
+                    if date < today || is_done {
+                        due += 1;
+                    }
+                    if is_done {
+                        done += 1;
+                    }
+                }
+                (due, done)
+            }
+        }
+    }
+
+    // 还在待办列表中，但已经没有需要完成的日期
+    fn is_expired(&self) -> bool {
+        if self.archived {
+            return false;
+        }
+        let todo = &self.todo;
+        match todo.kind {
+            TodoKind::Once => self.done.is_empty() && todo.once.to_naive_date() < get_today(),
+            TodoKind::Progress => {
+                self.done.is_empty() && todo.end_date.to_naive_date() < get_today()
+            }
+            _ => todo.end_date.to_naive_date() < get_today(),
+        }
+    }
+
+    // (当前连续完成的天数, 最长连续完成的天数)，今天还没完成时从昨天开始算
+    fn get_streak(&self) -> (i32, i32) {
+        let dates = self.done.iter().copied().collect::<BTreeSet<NaiveDate>>();
+        let mut longest = 0;
+        let mut length = 0;
+        let mut last: Option<NaiveDate> = None;
+        for date in &dates {
+            length = match last {
+                Some(last) if last.succ_opt() == Some(*date) => length + 1,
+                _ => 1,
+            };
+            longest = longest.max(length);
+            last = Some(*date);
+        }
+        let mut date = get_today();
+        if !dates.contains(&date) {
+            date = date.pred_opt().unwrap();
+        }
+        let mut current = 0;
+        while dates.contains(&date) {
+            current += 1;
+            date = date.pred_opt().unwrap();
+        }
+        (current, longest)
+    }
+
+    // 有完成记录或者进度变化的日期
+    fn get_activity_dates(&self) -> impl Iterator<Item = NaiveDate> + '_ {
+        self.done.iter().copied().chain(
+            self.progress
+                .iter()
+                .filter(|r| r.delta != 0)
+                .map(|r| r.date.to_naive_date()),
+        )
+    }
+}
+
+pub fn set_stats_logic(app: Weak<AppWindow>) {
+    let app = app.unwrap();
+    let weak = app.as_weak();
+    app.global::<StatsData>()
+        .on_refresh_stats(move || refresh_stats(&weak.unwrap()));
+}
+
+// 打开统计页面时重新计算
+pub fn refresh_stats(app: &AppWindow) {
+    let mut entries = TODOS_MODEL.with(|todos_model| todos_model.borrow().to_stats_entries());
+    entries.extend(TODO_ARCHIVE.with(|todo_archive| todo_archive.borrow().to_stats_entries()));
+    // 按创建顺序排列，结果不受HashMap顺序影响
+    entries.sort_by(|a, b| a.todo.id.as_str().cmp(b.todo.id.as_str()));
+    let stats_data = app.global::<StatsData>();
+    let kind_stats = get_kind_stats(&entries);
+    stats_data.set_total_todos(kind_stats.iter().map(|s| s.total).sum());
+    stats_data.set_expired_count(kind_stats.iter().map(|s| s.expired).sum());
+    stats_data.set_archived_count(entries.iter().filter(|e| e.archived).count() as i32);
+    stats_data.set_kind_stats(to_model(kind_stats));
+    stats_data.set_streaks(to_model(get_streaks(&entries)));
+    let activity = get_activity(&entries);
+    stats_data.set_weekly_heatmap(to_model(get_weekly_heatmap(&activity)));
+    stats_data.set_monthly_heatmap(to_model(get_monthly_heatmap(&activity)));
+    stats_data.set_progress_stats(to_model(get_progress_stats(&entries)));
+}
+
+fn to_model<T: Clone + 'static>(vec: Vec<T>) -> ModelRc<T> {
+    Rc::new(VecModel::from(vec)).into()
+}
+
+fn get_kind_stats(entries: &[StatsEntry]) -> Vec<KindStat> {
+    KINDS
+        .iter()
+        .map(|kind| {
+            let mut stat = KindStat {
+                kind: *kind,
+                ..Default::default()
+            };
+            for entry in entries.iter().filter(|e| e.todo.kind == *kind) {
+                let (due, done) = entry.count_occurrences();
+                stat.total += 1;
+                stat.due += due;
+                stat.done += done;
+                stat.expired += entry.is_expired() as i32;
+            }
+            stat.rate = if stat.due > 0 {
+                stat.done as f32 / stat.due as f32
+            } else {
+                0.0
+            };
+            stat
+        })
+        .collect()
+}
+
+// 待办列表中每日待办的连续完成天数，当前连续天数多的排在前面
+fn get_streaks(entries: &[StatsEntry]) -> Vec<StreakStat> {
+    let mut streaks = entries
+        .iter()
+        .filter(|e| !e.archived && e.todo.kind == TodoKind::Daily)
+        .map(|e| {
+            let (current, longest) = e.get_streak();
+            StreakStat {
+                text: e.todo.text.clone(),
+                current,
+                longest,
+            }
+        })
+        .collect::<Vec<StreakStat>>();
+    streaks.sort_by(|a, b| b.current.cmp(&a.current).then(b.longest.cmp(&a.longest)));
+    streaks
+}
+
+// 每天完成和更新进度的次数
+fn get_activity(entries: &[StatsEntry]) -> HashMap<NaiveDate, i32> {
+    let mut activity = HashMap::new();
+    for date in entries.iter().flat_map(StatsEntry::get_activity_dates) {
+        *activity.entry(date).or_insert(0) += 1;
+    }
+    activity
+}
+
+// 按最大值把次数分为HEATMAP_LEVELS级
+fn get_level(count: i32, max: i32) -> i32 {
+    if count <= 0 || max <= 0 {
+        0
+    } else {
+        (count * HEATMAP_LEVELS + max - 1) / max
+    }
+}
+
+// 最近HEATMAP_WEEKS周，每周从周一到周日，今天之后的日期level为-1
+fn get_weekly_heatmap(activity: &HashMap<NaiveDate, i32>) -> Vec<HeatmapWeek> {
+    let today = get_today();
+    let this_monday = today - Days::new(today.weekday().num_days_from_monday() as u64);
+    let start = this_monday - Days::new((HEATMAP_WEEKS - 1) * 7);
+    let max = start
+        .iter_days()
+        .take_while(|d| *d <= today)
+        .map(|d| activity.get(&d).copied().unwrap_or(0))
+        .max()
+        .unwrap_or(0);
+    (0..HEATMAP_WEEKS)
+        .map(|week| {
+            let days = (0..7)
+                .map(|day| {
+                    let date = start + Days::new(week * 7 + day);
+                    let count = activity.get(&date).copied().unwrap_or(0);
+                    HeatmapDay {
+                        date: SlintDate::from_naive_date(&date),
+                        count,
+                        level: if date > today {
+                            -1
+                        } else {
+                            get_level(count, max)
+                        },
+                    }
+                })
+                .collect::<Vec<HeatmapDay>>();
+            HeatmapWeek {
+                days: to_model(days),
+            }
+        })
+        .collect()
+}
+
+// 最近HEATMAP_MONTHS个月，从早到晚排列
+fn get_monthly_heatmap(activity: &HashMap<NaiveDate, i32>) -> Vec<MonthActivity> {
+    let this_month = get_today().with_day(1).unwrap();
+    let months = (0..HEATMAP_MONTHS)
+        .rev()
+        .map(|i| {
+            let month = this_month - Months::new(i);
+            let count = activity
+                .iter()
+                .filter(|(date, _)| date.year() == month.year() && date.month() == month.month())
+                .map(|(_, count)| count)
+                .sum::<i32>();
+            (month, count)
+        })
+        .collect::<Vec<(NaiveDate, i32)>>();
+    let max = months.iter().map(|(_, count)| *count).max().unwrap_or(0);
+    months
+        .into_iter()
+        .map(|(month, count)| MonthActivity {
+            text: month.format("%Y-%m").to_string().into(),
+            count,
+            level: get_level(count, max),
+        })
+        .collect()
+}
+
+// 待办列表中的进度待办，按平均速度估计还需要多少天完成，无法估计时为-1
+fn get_progress_stats(entries: &[StatsEntry]) -> Vec<ProgressStat> {
+    entries
+        .iter()
+        .filter(|e| !e.archived && e.todo.kind == TodoKind::Progress)
+        .map(|e| {
+            let velocity = get_progress_velocity(&e.progress);
+            let remaining = e.todo.max_progress - e.todo.current_progress;
+            let remaining_days = if remaining <= 0 {
+                0
+            } else if velocity > 0.0 {
+                (remaining as f32 / velocity).ceil() as i32
+            } else {
+                -1
+            };
+            ProgressStat {
+                text: e.todo.text.clone(),
+                current: e.todo.current_progress,
+                max: e.todo.max_progress,
+                velocity,
+                remaining_days,
+            }
+        })
+        .collect()
+}
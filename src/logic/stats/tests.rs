@@ -0,0 +1,89 @@
+use crate::{
+    Todo, TodoKind,
+    logic::{
+        SlintDate,
+        stats::{StatsEntry, get_level},
+        todo::get_today,
+    },
+};
+use chrono::{Datelike, Days, NaiveDate};
+
+// 距离今天days天的日期，负数表示之前
+fn day(days: i64) -> NaiveDate {
+    let today = get_today();
+    if days >= 0 {
+        today + Days::new(days as u64)
+    } else {
+        today - Days::new(-days as u64)
+    }
+}
+
+fn entry(todo: Todo, done: &[i64]) -> StatsEntry {
+    StatsEntry {
+        todo,
+        done: done.iter().map(|d| day(*d)).collect(),
+        progress: vec![],
+        archived: false,
+    }
+}
+
+fn once(date: i64) -> Todo {
+    Todo {
+        kind: TodoKind::Once,
+        once: SlintDate::from_naive_date(&day(date)),
+        ..Default::default()
+    }
+}
+
+fn repeated(kind: TodoKind, start: i64, end: i64) -> Todo {
+    Todo {
+        kind,
+        start_date: SlintDate::from_naive_date(&day(start)),
+        end_date: SlintDate::from_naive_date(&day(end)),
+        ..Default::default()
+    }
+}
+
+#[test]
+fn once_occurrences() {
+    assert_eq!(entry(once(-1), &[]).count_occurrences(), (1, 0));
+    assert_eq!(entry(once(0), &[]).count_occurrences(), (0, 0));
+    assert_eq!(entry(once(0), &[0]).count_occurrences(), (1, 1));
+    assert_eq!(entry(once(2), &[]).count_occurrences(), (0, 0));
+}
+
+#[test]
+fn daily_occurrences() {
+    // 之前的6天都要完成，今天已经完成时也计入，之后的日期不计入
+    let todo = repeated(TodoKind::Daily, -6, 3);
+    assert_eq!(entry(todo.clone(), &[-3, -1]).count_occurrences(), (6, 2));
+    assert_eq!(entry(todo, &[-3, -1, 0]).count_occurrences(), (7, 3));
+}
+
+#[test]
+fn weekly_occurrences() {
+    let mut todo = repeated(TodoKind::Weekly, -14, -1);
+    todo.week = day(-1).weekday().into();
+    assert_eq!(entry(todo, &[-8]).count_occurrences(), (2, 1));
+}
+
+#[test]
+fn streak() {
+    let todo = repeated(TodoKind::Daily, -30, 30);
+    let done = [-14, -13, -12, -11, -10, -2, -1, 0];
+    assert_eq!(entry(todo.clone(), &done).get_streak(), (3, 5));
+    // 今天还没完成时从昨天开始算
+    assert_eq!(entry(todo.clone(), &[-2, -1]).get_streak(), (2, 2));
+    assert_eq!(entry(todo.clone(), &[-3, -2]).get_streak(), (0, 2));
+    assert_eq!(entry(todo, &[]).get_streak(), (0, 0));
+}
+
+#[test]
+fn level() {
+    assert_eq!(get_level(0, 10), 0);
+    assert_eq!(get_level(1, 10), 1);
+    assert_eq!(get_level(3, 10), 2);
+    assert_eq!(get_level(10, 10), 4);
+    assert_eq!(get_level(1, 1), 4);
+    assert_eq!(get_level(5, 0), 0);
+}
@@ -0,0 +1,193 @@
+use crate::logic::APP_PATH;
+use chrono::Local;
+use serde::{Serialize, de::DeserializeOwned};
+use serde_json::Value;
+use std::{
+    fs::File,
+    io::{BufReader, BufWriter, Write},
+    path::{Path, PathBuf},
+    sync::atomic::{AtomicU64, Ordering},
+};
+
+// 每个数据文件最多保留的备份数量
+const BACKUP_COUNT: usize = 10;
+// 临时文件的序号，同一个文件同时有多次保存时各自写入不同的临时文件
+static TMP_COUNTER: AtomicU64 = AtomicU64::new(0);
+
+// 数据文件的迁移函数，把第i版的数据迁移到第i+1版
+pub type Migration = fn(Value) -> Result<Value, String>;
+
+// 覆盖前备份原文件，每个数据文件各自保留最近的BACKUP_COUNT份
+pub fn save_json<T: Serialize + ?Sized>(path: &Path, value: &T) -> std::io::Result<()> {
+    if path.exists()
+        && let Err(err) = backup_file(path)
+    {
+        eprintln!("{}备份失败：{}", path.display(), err);
+    }
+    save_json_without_backup(path, value)
+}
+
+// 先写入临时文件再重命名覆盖原文件，保存过程中崩溃或磁盘写满都不会破坏原文件
+// 不备份，用于频繁更新、丢失后也可以重新生成的状态文件
+pub fn save_json_without_backup<T: Serialize + ?Sized>(
+    path: &Path,
+    value: &T,
+) -> std::io::Result<()> {
+    let tmp_path = get_tmp_path(path);
+    let mut writer = BufWriter::new(File::create(&tmp_path)?);
+    serde_json::to_writer(&mut writer, value)?;
+    writer.flush()?;
+    writer.get_ref().sync_all()?;
+    drop(writer);
+    std::fs::rename(&tmp_path, path)
+}
+
+// 文件不存在时返回None，文件损坏时依次尝试从临时文件和备份中恢复
+pub fn load_json<T: DeserializeOwned>(path: &Path) -> Option<T> {
+    if !path.exists() {
+        return None;
+    }
+    let err = match read_json(path) {
+        Ok(value) => return Some(value),
+        Err(err) => err,
+    };
+    eprintln!("{}解析失败：{}", path.display(), err);
+    // 保留损坏的文件，避免之后被覆盖
+    let corrupt_path = path.with_extension(format!(
+        "json.corrupt-{}",
+        Local::now().format("%Y%m%d%H%M%S")
+    ));
+    if let Err(err) = std::fs::rename(path, &corrupt_path) {
+        eprintln!("{}无法移动：{}", path.display(), err);
+    }
+    let candidates = get_tmp_paths(path)
+        .into_iter()
+        .chain(get_backup_paths(path));
+    for candidate in candidates {
+        if let Ok(value) = read_json(&candidate) {
+            eprintln!("已从{}恢复{}", candidate.display(), path.display());
+            if let Err(err) = std::fs::copy(&candidate, path) {
+                eprintln!("{}恢复失败：{}", path.display(), err);
+            }
+            return Some(value);
+        }
+    }
+    None
+}
+
+// 备份当前文件，和最近一次备份内容相同时跳过，只保留最近的BACKUP_COUNT份
+fn backup_file(path: &Path) -> std::io::Result<()> {
+    let backup_dir = get_backup_dir();
+    if !backup_dir.exists() {
+        std::fs::create_dir_all(&backup_dir)?;
+    }
+    let content = std::fs::read(path)?;
+    let backups = get_backup_paths(path);
+    if let Some(latest) = backups.first()
+        && std::fs::read(latest).is_ok_and(|c| c == content)
+    {
+        return Ok(());
+    }
+    let backup_path = backup_dir.join(format!(
+        "{}-{}.json",
+        get_file_stem(path),
+        Local::now().format("%Y%m%d%H%M%S")
+    ));
+    std::fs::write(backup_path, content)?;
+    for old_backup in get_backup_paths(path).into_iter().skip(BACKUP_COUNT) {
+        std::fs::remove_file(old_backup)?;
+    }
+    Ok(())
+}
+
+// 把带版本号的数据依次迁移到当前版本，当前版本就是migrations的长度
+// 没有版本号的数组是第0版，name用于错误信息，如"待办数据"
+pub fn load_versioned(
+    mut value: Value,
+    migrations: &[Migration],
+    name: &str,
+) -> Result<Value, String> {
+    let current_version = migrations.len() as u32;
+    let mut version = match &value {
+        Value::Array(_) => 0,
+        Value::Object(map) => map
+            .get("version")
+            .and_then(Value::as_u64)
+            .ok_or(format!("{}缺少版本号", name))? as u32,
+        _ => return Err(format!("无法识别的{}格式", name)),
+    };
+    if version > current_version {
+        return Err(format!(
+            "{}版本{}高于当前支持的版本{}",
+            name, version, current_version
+        ));
+    }
+    while version < current_version {
+        value = migrations[version as usize](value)?;
+        version += 1;
+    }
+    Ok(value)
+}
+
+// FNV-1a哈希，结果不随Rust版本变化，可以保存到文件中
+pub fn hash_bytes(bytes: &[u8]) -> String {
+    let hash = bytes.iter().fold(0xcbf29ce484222325u64, |hash, b| {
+        (hash ^ *b as u64).wrapping_mul(0x100000001b3)
+    });
+    format!("{:016x}", hash)
+}
+
+fn read_json<T: DeserializeOwned>(path: &Path) -> std::io::Result<T> {
+    let reader = BufReader::new(File::open(path)?);
+    Ok(serde_json::from_reader(reader)?)
+}
+
+fn get_tmp_path(path: &Path) -> PathBuf {
+    let count = TMP_COUNTER.fetch_add(1, Ordering::Relaxed);
+    path.with_extension(format!("json.tmp-{}-{}", std::process::id(), count))
+}
+
+// 保存中断时留下的临时文件，按修改时间从新到旧排列
+fn get_tmp_paths(path: &Path) -> Vec<PathBuf> {
+    let prefix = format!("{}.json.tmp-", get_file_stem(path));
+    let Some(Ok(entries)) = path.parent().map(std::fs::read_dir) else {
+        return vec![];
+    };
+    let mut tmp_paths = entries
+        .filter_map(|e| e.ok())
+        .filter(|e| e.file_name().to_string_lossy().starts_with(&prefix))
+        .filter_map(|e| Some((e.metadata().ok()?.modified().ok()?, e.path())))
+        .collect::<Vec<_>>();
+    tmp_paths.sort();
+    tmp_paths.into_iter().rev().map(|(_, p)| p).collect()
+}
+
+fn get_backup_dir() -> PathBuf {
+    APP_PATH.join("data").join("backups")
+}
+
+fn get_file_stem(path: &Path) -> String {
+    path.file_stem()
+        .map(|s| s.to_string_lossy().to_string())
+        .unwrap_or_default()
+}
+
+// 备份文件名带有时间，按文件名倒序排列就是从新到旧
+fn get_backup_paths(path: &Path) -> Vec<PathBuf> {
+    let prefix = format!("{}-", get_file_stem(path));
+    let Ok(entries) = std::fs::read_dir(get_backup_dir()) else {
+        return vec![];
+    };
+    let mut backups = entries
+        .filter_map(|e| e.ok())
+        .map(|e| e.path())
+        .filter(|p| {
+            p.file_name()
+                .map(|n| n.to_string_lossy())
+                .is_some_and(|n| n.starts_with(&prefix) && n.ends_with(".json"))
+        })
+        .collect::<Vec<PathBuf>>();
+    backups.sort();
+    backups.reverse();
+    backups
+}
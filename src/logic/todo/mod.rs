@@ -0,0 +1,1026 @@
+use crate::{
+    AppWindow, CalendarDay, Filter, ProgressRecord, Todo, TodoData, TodoKind,
+    logic::{
+        APP_PATH, SlintDate,
+        archive::ArchivedTodo,
+        record::{TodoFile, TodoRecord},
+        reminder::ReminderEntry,
+        stats::StatsEntry,
+        storage::{load_json, save_json},
+    },
+};
+use chrono::{Datelike, Days, Local, NaiveDate, Utc, Weekday};
+use slint::{ComponentHandle, Model, ModelRc, SharedString, VecModel, Weak};
+use std::{
+    cell::RefCell,
+    collections::{BTreeMap, HashMap, HashSet},
+    rc::Rc,
+    sync::atomic::{AtomicI64, Ordering},
+};
+
+#[cfg(test)]
+mod tests;
+
+// 每次调用时重新获取，程序跨过午夜后日期随之变化
+pub fn get_today() -> NaiveDate {
+    Local::now().date_naive()
+}
+pub const WEEKDAY: [Weekday; 7] = [
+    Weekday::Mon,
+    Weekday::Tue,
+    Weekday::Wed,
+    Weekday::Thu,
+    Weekday::Fri,
+    Weekday::Sat,
+    Weekday::Sun,
+];
+// 待办id的位数，id是补零的微秒时间戳，按字符串排序就是按生成的先后排序
+const TODO_ID_WIDTH: usize = 20;
+// 上一次生成的待办id，保证同一时刻或批量导入时生成的id也不会重复
+static LAST_TODO_ID: AtomicI64 = AtomicI64::new(0);
+
+thread_local! {
+    pub static TODOS_MODEL: Rc<RefCell<TodosModel>> = {
+    let path = APP_PATH.join("data").join("todo_list.json");
+    let mut migrated_ids = HashMap::new();
+    let mut todos_model = TodosModel::new(get_today());
+    // 文件损坏时load_json会尝试从备份恢复，都失败时从空列表开始
+    // 旧版本的数据在反序列化时会按版本号依次迁移
+    if let Some(TodoFile { todos: mut records, .. }) = load_json::<TodoFile>(&path) {
+        // 旧版本以秒级时间戳作为id，加载时迁移为新的id
+        migrated_ids = migrate_todo_ids(&mut records);
+        todos_model.load_todos_to_model(records.into_iter().map(Todo::from).collect());
+    }
+    let done_path = APP_PATH.join("data").join("todo_done.json");
+    if let Some(id_done) = load_json::<HashMap<String, Vec<SlintDate>>>(&done_path) {
+        todos_model.load_done_to_model(rename_todo_ids(id_done, &migrated_ids));
+    }
+    let progress_path = APP_PATH.join("data").join("todo_progress.json");
+    if let Some(id_progress) = load_json::<HashMap<String, Vec<ProgressRecord>>>(&progress_path) {
+        todos_model.load_progress_to_model(rename_todo_ids(id_progress, &migrated_ids));
+    }
+    // 只有id变化时才需要写回，数据没有变化时不重写文件，也不会产生重复的备份
+    if !migrated_ids.is_empty() {
+        todos_model.save_todos();
+        todos_model.save_done();
+        todos_model.save_progress();
+    }
+    Rc::new(RefCell::new(todos_model))
+};
+}
+
+#[derive(Debug, Default)]
+pub struct TodosModel {
+    // 日历显示的月份，默认是当前日期所在月份
+    selected_date: NaiveDate,
+    // 每个待办id要在一个月内哪些日期中显示
+    id_date_map: HashMap<String, Vec<NaiveDate>>,
+    // 每个id对应哪条待办
+    id_todo_map: HashMap<String, Rc<RefCell<Todo>>>,
+    // 一个月内每个日期包括哪些待办
+    date_todo_map: HashMap<NaiveDate, Vec<Rc<RefCell<Todo>>>>,
+    // 一个月内星期一至星期天对应哪些日期
+    week_day_map: HashMap<Weekday, Vec<u32>>,
+    // 每个待办id已经完成的日期，重复待办按每次发生的日期分别记录
+    id_done_map: HashMap<String, Vec<NaiveDate>>,
+    // 每个进度待办的进度变化记录，同一天的多次变化合并为一条
+    id_progress_map: HashMap<String, Vec<ProgressRecord>>,
+}
+
+impl TodosModel {
+    fn new(selected_date: NaiveDate) -> Self {
+        let id_map = HashMap::new();
+        let mut date_calendar_map = HashMap::new();
+        for d in 1..=selected_date.num_days_in_month() {
+            let date =
+                NaiveDate::from_ymd_opt(selected_date.year(), selected_date.month(), d as u32);
+            date_calendar_map.insert(date.unwrap(), vec![]);
+        }
+        let mut week_calendar_map = HashMap::new();
+        let days = match_week_with_day(selected_date);
+        for (w, d) in WEEKDAY.iter().zip(days) {
+            week_calendar_map.insert(*w, d);
+        }
+        let id_dates = HashMap::new();
+        TodosModel {
+            id_todo_map: id_map,
+            date_todo_map: date_calendar_map,
+            week_day_map: week_calendar_map,
+            selected_date,
+            id_date_map: id_dates,
+            id_done_map: HashMap::new(),
+            id_progress_map: HashMap::new(),
+        }
+    }
+
+    fn load_todos_to_model(&mut self, todos: Vec<Todo>) {
+        for mut todo in todos {
+            //加载时顺便检测是否过期
+            if todo.calculate_days_to_start().is_none() {
+                todo.is_expired = true;
+            } 
+            self.insert_todo_to_model(todo);
+        }
+    }
+
+    fn load_done_to_model(&mut self, id_done: HashMap<String, Vec<SlintDate>>) {
+        for (id, dates) in id_done {
+            // 已删除的待办不再保留完成记录
+            if !self.id_todo_map.contains_key(&id) {
+                continue;
+            }
+            let dates = dates.iter().map(|d| d.to_naive_date()).collect();
+            self.id_done_map.insert(id, dates);
+        }
+    }
+
+    fn load_progress_to_model(&mut self, id_progress: HashMap<String, Vec<ProgressRecord>>) {
+        for (id, records) in id_progress {
+            if self.id_todo_map.contains_key(&id) {
+                self.id_progress_map.insert(id, records);
+            }
+        }
+    }
+
+    // 单次和进度待办只要有完成记录就算完成，重复待办只看对应日期那一次
+    fn is_done_on(&self, todo: &Todo, date: NaiveDate) -> bool {
+        match self.id_done_map.get(&todo.id.to_string()) {
+            Some(dates) => match todo.kind {
+                TodoKind::Once | TodoKind::Progress => !dates.is_empty(),
+                _ => dates.contains(&date),
+            },
+            None => false,
+        }
+    }
+
+    pub fn toggle_todo_done(&mut self, id: SharedString) {
+        let Some(todo) = self.id_todo_map.get(&id.to_string()).cloned() else {
+            return;
+        };
+        let todo = todo.borrow();
+        let date = todo.next_occurrence();
+        let is_done = self.is_done_on(&todo, date);
+        let dates = self.id_done_map.entry(id.to_string()).or_default();
+        match (todo.kind, is_done) {
+            (TodoKind::Once | TodoKind::Progress, true) => dates.clear(),
+            (_, true) => dates.retain(|d| *d != date),
+            (_, false) => dates.push(date),
+        }
+        if dates.is_empty() {
+            self.id_done_map.remove(&id.to_string());
+        }
+        self.save_done();
+    }
+
+    // 已完成的单次和进度待办，以及已经过期的待办都可以归档
+    fn is_archivable(&self, todo: &Todo) -> bool {
+        let is_done = self.is_done_on(todo, todo.next_occurrence());
+        match todo.kind {
+            TodoKind::Once => is_done || todo.once.to_naive_date() < get_today(),
+            TodoKind::Progress => is_done || todo.end_date.to_naive_date() < get_today(),
+            _ => todo.clone().calculate_days_to_start().is_none(),
+        }
+    }
+
+    pub fn take_archivable_todos(&mut self) -> Vec<ArchivedTodo> {
+        let ids = self
+            .id_todo_map
+            .iter()
+            .filter(|(_, t)| self.is_archivable(&t.borrow()))
+            .map(|(id, _)| id.clone())
+            .collect::<Vec<String>>();
+        let archived_at = get_today().format("%Y-%m-%d").to_string();
+        let mut archived_todos = vec![];
+        for id in ids {
+            let todo = self.id_todo_map.remove(&id).unwrap();
+            for date in self.id_date_map.remove(&id).unwrap_or_default() {
+                self.date_todo_map
+                    .get_mut(&date)
+                    .unwrap()
+                    .retain(|t| t.borrow().id.as_str() != id.as_str());
+            }
+            let done = self
+                .id_done_map
+                .remove(&id)
+                .unwrap_or_default()
+                .iter()
+                .map(SlintDate::from_naive_date)
+                .collect();
+            let progress = self.id_progress_map.remove(&id).unwrap_or_default();
+            archived_todos.push(ArchivedTodo {
+                todo: TodoRecord::from(&*todo.borrow()),
+                archived_at: archived_at.clone(),
+                done,
+                progress,
+            });
+        }
+        if !archived_todos.is_empty() {
+            self.save_todos();
+            self.save_done();
+            self.save_progress();
+        }
+        archived_todos
+    }
+
+    pub fn restore_todo_to_model(&mut self, archived_todo: ArchivedTodo) {
+        let ArchivedTodo {
+            todo,
+            done,
+            progress,
+            ..
+        } = archived_todo;
+        let mut todo = Todo::from(todo);
+        let id = todo.id.to_string();
+        todo.is_expired = todo.calculate_days_to_start().is_none();
+        self.add_todo_model(todo);
+        if !done.is_empty() {
+            let done = done.iter().map(|d| d.to_naive_date()).collect();
+            self.id_done_map.insert(id.clone(), done);
+            self.save_done();
+        }
+        if !progress.is_empty() {
+            self.id_progress_map.insert(id, progress);
+            self.save_progress();
+        }
+    }
+
+    pub fn set_progress_in_model(&mut self, id: SharedString, value: i32) {
+        let Some(todo) = self.id_todo_map.get(&id.to_string()).cloned() else {
+            return;
+        };
+        if todo.borrow().kind != TodoKind::Progress {
+            return;
+        }
+        let max_progress = todo.borrow().max_progress;
+        let value = value.clamp(0, max_progress);
+        let delta = value - todo.borrow().current_progress;
+        if delta == 0 {
+            return;
+        }
+        todo.borrow_mut().current_progress = value;
+        let today = SlintDate::from_naive_date(&get_today());
+        let records = self.id_progress_map.entry(id.to_string()).or_default();
+        match records.last_mut() {
+            Some(record) if record.date == today => {
+                record.value = value;
+                record.delta += delta;
+            }
+            _ => records.push(ProgressRecord {
+                date: today,
+                value,
+                delta,
+            }),
+        }
+        self.sync_progress_done(&id, value, max_progress);
+        self.save_todos();
+        self.save_done();
+        self.save_progress();
+    }
+
+    // 进度达到最大值时自动完成，回退到最大值以下时取消完成
+    fn sync_progress_done(&mut self, id: &str, value: i32, max_progress: i32) {
+        if value >= max_progress {
+            self.id_done_map
+                .entry(id.to_string())
+                .or_insert_with(|| vec![get_today()]);
+        } else {
+            self.id_done_map.remove(id);
+        }
+    }
+
+    pub fn increase_progress_in_model(&mut self, id: SharedString, delta: i32) {
+        let current_progress = match self.id_todo_map.get(&id.to_string()) {
+            Some(todo) => todo.borrow().current_progress,
+            None => return,
+        };
+        self.set_progress_in_model(id, current_progress + delta);
+    }
+
+    pub fn to_progress_log_model(&self, id: SharedString) -> ModelRc<ProgressRecord> {
+        let records = self
+            .id_progress_map
+            .get(&id.to_string())
+            .cloned()
+            .unwrap_or_default();
+        Rc::new(VecModel::from(records)).into()
+    }
+
+    pub fn get_progress_velocity(&self, id: SharedString) -> f32 {
+        match self.id_progress_map.get(&id.to_string()) {
+            Some(records) => get_progress_velocity(records),
+            None => 0.0,
+        }
+    }
+
+    // 统计页面使用的待办以及完成记录和进度记录
+    pub fn to_stats_entries(&self) -> Vec<StatsEntry> {
+        self.id_todo_map
+            .iter()
+            .map(|(id, todo)| StatsEntry {
+                todo: todo.borrow().clone(),
+                done: self.id_done_map.get(id).cloned().unwrap_or_default(),
+                progress: self.id_progress_map.get(id).cloned().unwrap_or_default(),
+                archived: false,
+            })
+            .collect()
+    }
+
+    // 设置了提醒时间的待办
+    pub fn to_reminder_entries(&self) -> Vec<ReminderEntry> {
+        self.id_todo_map
+            .iter()
+            .filter(|(_, todo)| !todo.borrow().remind_time.is_empty())
+            .map(|(id, todo)| ReminderEntry {
+                todo: todo.borrow().clone(),
+                done: self.id_done_map.get(id).cloned().unwrap_or_default(),
+            })
+            .collect()
+    }
+
+    pub fn get_selected_date(&self) -> SlintDate {
+        SlintDate::from_naive_date(&self.selected_date)
+    }
+    pub fn to_todo_list_model(&self) -> ModelRc<Todo> {
+        let mut todos_vec = self
+            .id_todo_map
+            .values()
+            .map(|t| {
+                let mut todo = t.borrow().clone();
+                todo.is_done = self.is_done_on(&todo, todo.next_occurrence());
+                todo
+            })
+            .collect::<Vec<Todo>>();
+        todos_vec.sort_by(|a, b| {
+            a.created_at
+                .as_str()
+                .cmp(b.created_at.as_str())
+                .then_with(|| a.id.as_str().cmp(b.id.as_str()))
+        });
+        let modelrc = Rc::new(VecModel::from(todos_vec));
+        modelrc.into()
+    }
+
+    pub fn to_calendar_model(&self) -> ModelRc<CalendarDay> {
+        let mut calendar = vec![];
+        let start =
+            NaiveDate::from_ymd_opt(self.selected_date.year(), self.selected_date.month(), 1)
+                .unwrap();
+        let week_of_start = start.weekday().num_days_from_monday();
+        let days_of_month = self.selected_date.num_days_in_month();
+        for _ in 0..week_of_start {
+            calendar.push(CalendarDay::default());
+        }
+        for i in 1..=days_of_month {
+            let date = NaiveDate::from_ymd_opt(
+                self.selected_date.year(),
+                self.selected_date.month(),
+                i as u32,
+            )
+            .unwrap();
+            let todo_list = self
+                .date_todo_map
+                .get(&date)
+                .unwrap()
+                .iter()
+                .map(|t| {
+                    let mut todo = t.borrow().to_owned();
+                    todo.is_done = self.is_done_on(&todo, date);
+                    todo
+                })
+                .collect::<Vec<Todo>>();
+            let model = Rc::new(VecModel::from(todo_list)).into();
+            calendar.push(CalendarDay {
+                date: SlintDate::from_naive_date(&date),
+                todo_list: model,
+            });
+        }
+        while calendar.len() < 42 {
+            calendar.push(CalendarDay::default());
+        }
+        Rc::new(VecModel::from(calendar)).into()
+    }
+
+    pub fn remove_todo_from_model(&mut self, id: SharedString) {
+        if let Some(todo) = self.id_todo_map.remove(&id.to_string()) {
+            for date in self
+                .id_date_map
+                .get(&todo.borrow().id.to_string())
+                .unwrap()
+                .iter()
+            {
+                self.date_todo_map
+                    .get_mut(date)
+                    .unwrap()
+                    .retain(|t| t.borrow().id != todo.borrow().id);
+            }
+            self.id_date_map.remove(&todo.borrow().id.to_string());
+            self.save_todos();
+            if self.id_done_map.remove(&id.to_string()).is_some() {
+                self.save_done();
+            }
+            if self.id_progress_map.remove(&id.to_string()).is_some() {
+                self.save_progress();
+            }
+        }
+    }
+
+    pub fn update_todo_in_model(&mut self, mut todo: Todo) {
+        let id = todo.id.to_string();
+        let Some(old_todo) = self.id_todo_map.get(&id).cloned() else {
+            return;
+        };
+        // 编辑时保留原有的创建日期，并重新计算距离开始的天数和是否过期
+        todo.created_at = old_todo.borrow().created_at.clone();
+        todo.is_expired = todo.calculate_days_to_start().is_none();
+        // 进度的变化之后通过set_progress_in_model记录，这里先保持原来的进度
+        let new_progress = todo.current_progress;
+        todo.current_progress = old_todo.borrow().current_progress;
+        // 类型变化后原来的完成和进度记录不再适用，全部清除
+        if todo.kind != old_todo.borrow().kind {
+            todo.current_progress = 0;
+            if self.id_done_map.remove(&id).is_some() {
+                self.save_done();
+            }
+            if self.id_progress_map.remove(&id).is_some() {
+                self.save_progress();
+            }
+        }
+        // 先把旧的待办从日历中移除，再按新的重复规则重新匹配
+        if let Some(dates) = self.id_date_map.get(&id) {
+            for date in dates {
+                self.date_todo_map
+                    .get_mut(date)
+                    .unwrap()
+                    .retain(|t| t.borrow().id != todo.id);
+            }
+        }
+        self.id_date_map.insert(id.clone(), Vec::new());
+        let (kind, max_progress) = (todo.kind, todo.max_progress);
+        *old_todo.borrow_mut() = todo;
+        self.match_todo_with_calendar(old_todo.clone());
+        self.save_todos();
+        if kind == TodoKind::Progress {
+            self.set_progress_in_model(id.clone().into(), new_progress);
+            // 只修改了最大值时进度不变，也要重新判断是否完成
+            let value = old_todo.borrow().current_progress;
+            self.sync_progress_done(&id, value, max_progress);
+            self.save_done();
+        }
+    }
+
+    pub fn contains_todo(&self, id: &str) -> bool {
+        self.id_todo_map.contains_key(id)
+    }
+
+    pub fn add_todo_model(&mut self, todo: Todo) {
+        self.insert_todo_to_model(todo);
+        self.save_todos();
+    }
+
+    fn insert_todo_to_model(&mut self, todo: Todo) {
+        let todo = Rc::new(RefCell::new(todo));
+        self.id_todo_map
+            .insert(todo.borrow().id.to_string(), todo.clone());
+        self.id_date_map
+            .insert(todo.borrow().id.to_string(), Vec::new());
+        self.match_todo_with_calendar(todo);
+    }
+
+    fn match_todo_with_calendar(&mut self, todo: Rc<RefCell<Todo>>) {
+        match todo.borrow().kind {
+            TodoKind::Once => {
+                let once: NaiveDate = todo.borrow().once.to_naive_date();
+                if once.year() == self.selected_date.year()
+                    && once.month() == self.selected_date.month()
+                {
+                    self.id_date_map
+                        .get_mut(&todo.borrow().id.to_string())
+                        .unwrap()
+                        .push(once);
+                    self.date_todo_map
+                        .get_mut(&once)
+                        .unwrap()
+                        .push(todo.clone());
+                }
+            }
+            TodoKind::Daily | TodoKind::Progress => {
+                let start = todo.borrow().start_date.to_naive_date();
+                let end = todo.borrow().end_date.to_naive_date();
+                let keys = self
+                    .date_todo_map
+                    .keys()
+                    .copied()
+                    .collect::<Vec<NaiveDate>>();
+                for key in keys {
+                    if key < start || key > end {
+                        continue;
+                    }
+                    self.id_date_map
+                        .get_mut(&todo.borrow().id.to_string())
+                        .unwrap()
+                        .push(key);
+                    self.date_todo_map.get_mut(&key).unwrap().push(todo.clone())
+                }
+            }
+            TodoKind::Weekly => {
+                let start = todo.borrow().start_date.to_naive_date();
+                let end = todo.borrow().end_date.to_naive_date();
+                let week = todo.borrow().week.into();
+                if let Some(days) = self.week_day_map.get(&week) {
+                    for day in days {
+                        let date = NaiveDate::from_ymd_opt(
+                            self.selected_date.year(),
+                            self.selected_date.month(),
+                            *day,
+                        )
+                        .unwrap();
+                        if date < start || date > end {
+                            continue;
+                        }
+                        self.id_date_map
+                            .get_mut(&todo.borrow().id.to_string())
+                            .unwrap()
+                            .push(date);
+                        self.date_todo_map
+                            .get_mut(&date)
+                            .unwrap()
+                            .push(todo.clone());
+                    }
+                }
+            }
+            TodoKind::Monthly => {
+                let start = todo.borrow().start_date.to_naive_date();
+                let end = todo.borrow().end_date.to_naive_date();
+                let date = NaiveDate::from_ymd_opt(
+                    self.selected_date.year(),
+                    self.selected_date.month(),
+                    todo.borrow().day as u32,
+                )
+                .unwrap();
+                if date < start || date > end {
+                    return;
+                }
+                self.id_date_map
+                    .get_mut(&todo.borrow().id.to_string())
+                    .unwrap()
+                    .push(date);
+                self.date_todo_map
+                    .get_mut(&date)
+                    .unwrap()
+                    .push(todo.clone());
+            }
+        }
+    }
+
+    pub fn update_calendar_for_model(&mut self, date: NaiveDate) {
+        self.selected_date = date;
+        self.date_todo_map.clear();
+        self.week_day_map.clear();
+        self.id_date_map.clear();
+        for d in 1..=self.selected_date.num_days_in_month() {
+            let date = NaiveDate::from_ymd_opt(
+                self.selected_date.year(),
+                self.selected_date.month(),
+                d as u32,
+            );
+            self.date_todo_map.insert(date.unwrap(), vec![]);
+        }
+        let days = match_week_with_day(self.selected_date);
+        for (w, d) in WEEKDAY.iter().zip(days) {
+            self.week_day_map.insert(*w, d);
+        }
+        let todos = self.id_todo_map.values().cloned().collect::<Vec<_>>();
+        for todo in todos {
+            self.id_date_map
+                .insert(todo.borrow().id.to_string(), Vec::new());
+            self.match_todo_with_calendar(todo);
+        }
+    }
+
+    // 按id排序后保存，内容相同时文件也完全相同
+    fn save_todos(&self) {
+        let mut records = self
+            .id_todo_map
+            .values()
+            .map(|t| TodoRecord::from(&*t.borrow()))
+            .collect::<Vec<TodoRecord>>();
+        records.sort_by(|a, b| a.id.cmp(&b.id));
+        let path = APP_PATH.join("data").join("todo_list.json");
+        if let Err(err) = save_json(&path, &TodoFile::new(records)) {
+            eprintln!("保存待办失败：{}", err);
+        }
+    }
+
+    fn save_done(&self) {
+        let id_done = self
+            .id_done_map
+            .iter()
+            .map(|(id, dates)| {
+                let dates = dates.iter().map(SlintDate::from_naive_date).collect();
+                (id.clone(), dates)
+            })
+            .collect::<BTreeMap<String, Vec<SlintDate>>>();
+        let path = APP_PATH.join("data").join("todo_done.json");
+        if let Err(err) = save_json(&path, &id_done) {
+            eprintln!("保存完成记录失败：{}", err);
+        }
+    }
+
+    fn save_progress(&self) {
+        let path = APP_PATH.join("data").join("todo_progress.json");
+        let id_progress = self.id_progress_map.iter().collect::<BTreeMap<_, _>>();
+        if let Err(err) = save_json(&path, &id_progress) {
+            eprintln!("保存进度记录失败：{}", err);
+        }
+    }
+}
+
+pub fn set_todo_logic(app: Weak<AppWindow>) {
+    let app = app.unwrap();
+    let todo_data = app.global::<TodoData>();
+    let weak = app.as_weak();
+    todo_data.on_update_calendar(move |new_date: SlintDate| update_month(new_date, weak.clone()));
+    let weak = app.as_weak();
+    todo_data.on_add_todo(move |todo: Todo| add_todo(todo, weak.clone()));
+    let weak = app.as_weak();
+    todo_data.on_update_todo(move |todo: Todo| update_todo(todo, weak.clone()));
+    let weak = app.as_weak();
+    todo_data.on_remove_todo(move |id: SharedString| remove_todo(id, weak.clone()));
+    let weak = app.as_weak();
+    todo_data.on_toggle_todo_done(move |id: SharedString| toggle_todo_done(id, weak.clone()));
+    let weak = app.as_weak();
+    todo_data.on_update_progress(move |id: SharedString, delta: i32| {
+        update_progress(id, delta, weak.clone())
+    });
+    let weak = app.as_weak();
+    todo_data
+        .on_set_progress(move |id: SharedString, value: i32| set_progress(id, value, weak.clone()));
+    let weak = app.as_weak();
+    todo_data.on_load_progress_log(move |id: SharedString| load_progress_log(id, weak.clone()));
+    todo_data.on_filter_todos(filter_todo);
+    todo_data.on_duration_check(|mut todo| todo.calculate_days_to_start().is_some());
+}
+
+fn add_todo(todo: Todo, app: Weak<AppWindow>) {
+    let app = app.unwrap();
+    let todo_data = app.global::<TodoData>();
+    create_todo(todo);
+    todo_data
+        .set_todo_list(TODOS_MODEL.with(|todos_model| todos_model.borrow().to_todo_list_model()));
+    let new_calendar = TODOS_MODEL.with(|todos_model| todos_model.borrow().to_calendar_model());
+    todo_data.set_calendar(new_calendar);
+}
+
+fn update_todo(todo: Todo, app: Weak<AppWindow>) {
+    let app = app.unwrap();
+    let todo_data = app.global::<TodoData>();
+    TODOS_MODEL.with(|todos_model| todos_model.borrow_mut().update_todo_in_model(todo));
+    todo_data
+        .set_todo_list(TODOS_MODEL.with(|todos_model| todos_model.borrow().to_todo_list_model()));
+    let new_calendar = TODOS_MODEL.with(|todos_model| todos_model.borrow().to_calendar_model());
+    todo_data.set_calendar(new_calendar);
+}
+
+fn remove_todo(id: SharedString, app: Weak<AppWindow>) {
+    let app = app.unwrap();
+    let todo_data = app.global::<TodoData>();
+    TODOS_MODEL.with(|todos_model| todos_model.borrow_mut().remove_todo_from_model(id));
+    todo_data
+        .set_todo_list(TODOS_MODEL.with(|todos_model| todos_model.borrow().to_todo_list_model()));
+    let new_calendar = TODOS_MODEL.with(|todos_model| todos_model.borrow().to_calendar_model());
+    todo_data.set_calendar(new_calendar);
+}
+
+fn toggle_todo_done(id: SharedString, app: Weak<AppWindow>) {
+    let app = app.unwrap();
+    let todo_data = app.global::<TodoData>();
+    TODOS_MODEL.with(|todos_model| todos_model.borrow_mut().toggle_todo_done(id));
+    todo_data
+        .set_todo_list(TODOS_MODEL.with(|todos_model| todos_model.borrow().to_todo_list_model()));
+    let new_calendar = TODOS_MODEL.with(|todos_model| todos_model.borrow().to_calendar_model());
+    todo_data.set_calendar(new_calendar);
+}
+
+fn update_progress(id: SharedString, delta: i32, app: Weak<AppWindow>) {
+    TODOS_MODEL.with(|todos_model| {
+        todos_model
+            .borrow_mut()
+            .increase_progress_in_model(id.clone(), delta)
+    });
+    refresh_progress(id, app);
+}
+
+fn set_progress(id: SharedString, value: i32, app: Weak<AppWindow>) {
+    TODOS_MODEL.with(|todos_model| {
+        todos_model
+            .borrow_mut()
+            .set_progress_in_model(id.clone(), value)
+    });
+    refresh_progress(id, app);
+}
+
+fn refresh_progress(id: SharedString, app: Weak<AppWindow>) {
+    load_progress_log(id, app.clone());
+    let app = app.unwrap();
+    let todo_data = app.global::<TodoData>();
+    todo_data
+        .set_todo_list(TODOS_MODEL.with(|todos_model| todos_model.borrow().to_todo_list_model()));
+    let new_calendar = TODOS_MODEL.with(|todos_model| todos_model.borrow().to_calendar_model());
+    todo_data.set_calendar(new_calendar);
+}
+
+fn load_progress_log(id: SharedString, app: Weak<AppWindow>) {
+    let app = app.unwrap();
+    let todo_data = app.global::<TodoData>();
+    let (log, velocity) = TODOS_MODEL.with(|todos_model| {
+        let todos_model = todos_model.borrow();
+        (
+            todos_model.to_progress_log_model(id.clone()),
+            todos_model.get_progress_velocity(id),
+        )
+    });
+    todo_data.set_progress_log(log);
+    todo_data.set_progress_velocity(velocity);
+}
+
+// 分配id和创建日期后加入待办列表，返回新待办的id，其他模块创建待办时也使用这个函数
+pub fn create_todo(mut todo: Todo) -> SharedString {
+    todo.id = next_todo_id();
+    todo.created_at = get_today().format("%Y-%m-%d").to_string().into();
+    todo.calculate_days_to_start(); // TODO none返回错误
+    let id = todo.id.clone();
+    TODOS_MODEL.with(|todos_model| todos_model.borrow_mut().add_todo_model(todo));
+    id
+}
+
+// 待办变化后刷新界面上的待办列表和日历
+pub fn refresh_todo_data(app: &AppWindow) {
+    let todo_data = app.global::<TodoData>();
+    todo_data
+        .set_todo_list(TODOS_MODEL.with(|todos_model| todos_model.borrow().to_todo_list_model()));
+    let new_calendar = TODOS_MODEL.with(|todos_model| todos_model.borrow().to_calendar_model());
+    todo_data.set_calendar(new_calendar);
+}
+
+fn update_month(new_date: SlintDate, app: Weak<AppWindow>) {
+    let app = app.unwrap();
+    let todo_data = app.global::<TodoData>();
+    let new_calendar = TODOS_MODEL.with(|todos_model| {
+        todos_model.borrow_mut().update_calendar_for_model(new_date.to_naive_date());
+        todos_model.borrow().to_calendar_model()
+    });
+    todo_data.set_calendar(new_calendar);
+}
+pub fn init_todos(app: Weak<AppWindow>) {
+    let app = app.unwrap();
+    let todo_data = app.global::<TodoData>();
+    // 初始化新待办
+    let default_todo = todo_data.get_default_todo();
+    todo_data.set_new_todo(default_todo);
+    todo_data
+        .set_calendar(TODOS_MODEL.with(|todos_model| todos_model.borrow().to_calendar_model()));
+    todo_data
+        .set_todo_list(TODOS_MODEL.with(|todos_model| todos_model.borrow().to_todo_list_model()));
+    // 顺便初始化当前日期和当前选择日期
+    todo_data.set_current_date(SlintDate::from_naive_date(&get_today()));
+    todo_data.set_selected_date(
+        TODOS_MODEL.with(|todos_model| todos_model.borrow().get_selected_date()),
+    );
+}
+
+pub fn match_week_with_day(date: NaiveDate) -> Vec<Vec<u32>> {
+    let (year, month) = (date.year(), date.month());
+    // 创建7个数组对应周一到周日
+    let mut weekdays = Vec::new();
+
+    for _ in 0..7 {
+        weekdays.push(Vec::new());
+    }
+
+    // 获取该月第一天
+    let start_date = NaiveDate::from_ymd_opt(year, month, 1).unwrap();
+
+    // 获取下个月第一天
+    let next_month = if month == 12 {
+        NaiveDate::from_ymd_opt(year + 1, 1, 1).unwrap()
+    } else {
+        NaiveDate::from_ymd_opt(year, month + 1, 1).unwrap()
+    };
+
+    // 遍历该月的每一天
+    let mut current_date = start_date;
+    while current_date < next_month {
+        // weekday()返回0-6，其中0是周一
+        let weekday = current_date.weekday().num_days_from_monday() as usize;
+        weekdays[weekday].push(current_date.day());
+
+        // 前进一天
+        current_date = current_date.checked_add_days(Days::new(1)).unwrap();
+    }
+    weekdays
+}
+
+// 从第一条记录到今天平均每天的进度
+pub fn get_progress_velocity(records: &[ProgressRecord]) -> f32 {
+    let Some(first) = records.first() else {
+        return 0.0;
+    };
+    let days = get_today()
+        .signed_duration_since(first.date.to_naive_date())
+        .num_days()
+        + 1;
+    let total = records.iter().map(|r| r.delta).sum::<i32>();
+    total as f32 / days.max(1) as f32
+}
+
+pub fn next_todo_id() -> SharedString {
+    let now = Utc::now().timestamp_micros();
+    let last = LAST_TODO_ID
+        .fetch_update(Ordering::SeqCst, Ordering::SeqCst, |last| {
+            Some(now.max(last + 1))
+        })
+        .unwrap();
+    format!("{:0width$}", now.max(last + 1), width = TODO_ID_WIDTH).into()
+}
+
+fn is_valid_todo_id(id: &str) -> bool {
+    id.len() == TODO_ID_WIDTH && id.bytes().all(|b| b.is_ascii_digit()) && id.parse::<i64>().is_ok()
+}
+
+// 把旧格式的id和重复的id替换为新生成的id，返回旧id到新id的对应关系
+pub fn migrate_todo_ids<'a>(
+    todos: impl IntoIterator<Item = &'a mut TodoRecord>,
+) -> HashMap<String, String> {
+    let mut todos = todos.into_iter().collect::<Vec<&mut TodoRecord>>();
+    let mut used_ids = HashSet::new();
+    let mut invalid = vec![];
+    for (i, todo) in todos.iter().enumerate() {
+        if is_valid_todo_id(&todo.id) && used_ids.insert(todo.id.clone()) {
+            // 保证新生成的id比已有的都大
+            LAST_TODO_ID.fetch_max(todo.id.parse::<i64>().unwrap(), Ordering::SeqCst);
+        } else {
+            invalid.push(i);
+        }
+    }
+    // 按创建日期和旧id排序后再生成新id，保持原来的先后顺序
+    invalid.sort_by(|a, b| {
+        let (a, b) = (&todos[*a], &todos[*b]);
+        a.created_at
+            .as_str()
+            .cmp(b.created_at.as_str())
+            .then_with(|| a.id.as_str().cmp(b.id.as_str()))
+    });
+    let mut migrated_ids = HashMap::new();
+    for i in invalid {
+        let new_id = next_todo_id().to_string();
+        let old_id = std::mem::replace(&mut todos[i].id, new_id.clone());
+        // 重复的id无法区分原来的记录属于哪一条，只保留第一条的对应关系
+        if !used_ids.contains(&old_id) {
+            migrated_ids.entry(old_id).or_insert(new_id);
+        }
+    }
+    migrated_ids
+}
+
+fn rename_todo_ids<T>(
+    map: HashMap<String, T>,
+    migrated_ids: &HashMap<String, String>,
+) -> HashMap<String, T> {
+    map.into_iter()
+        .map(|(id, v)| match migrated_ids.get(&id) {
+            Some(new_id) => (new_id.clone(), v),
+            None => (id, v),
+        })
+        .collect()
+}
+
+impl Todo {
+    // 下一次需要完成的日期，单次待办就是指定日期，其余按距离开始的天数推算
+    fn next_occurrence(&self) -> NaiveDate {
+        match self.kind {
+            TodoKind::Once => self.once.to_naive_date(),
+            _ => get_today()
+                .checked_add_days(Days::new(self.days_to_start.max(0) as u64))
+                .unwrap(),
+        }
+    }
+
+    // 某一天是否需要完成这个待办，不考虑是否已经完成
+    pub fn occurs_on(&self, date: NaiveDate) -> bool {
+        let in_range =
+            self.start_date.to_naive_date() <= date && self.end_date.to_naive_date() >= date;
+        match self.kind {
+            TodoKind::Once => self.once.to_naive_date() == date,
+            TodoKind::Daily | TodoKind::Progress => in_range,
+            TodoKind::Weekly => in_range && self.week as u32 == date.weekday() as u32,
+            TodoKind::Monthly => in_range && self.day == date.day() as i32,
+        }
+    }
+
+    fn calculate_days_to_start(&mut self) -> Option<i32> {
+    let today = get_today();
+    let days = match self.kind {
+        TodoKind::Once => {
+            let date = self.once.to_naive_date();
+            let days = date.signed_duration_since(today).num_days() as i32;
+            self.days_to_start = days;
+            // 当天的单次待办还没有过期
+            if days >= 0 {
+                return Some(days);
+            } else {
+                return None;
+            }
+        }
+        TodoKind::Daily | TodoKind::Progress => 0,
+        TodoKind::Weekly => {
+            let weekday = self.week as i32;
+            let current_weekday = today.weekday() as i32;
+            if current_weekday > weekday {
+                7 - current_weekday + weekday
+            } else {
+                weekday - current_weekday
+            }
+        }
+        TodoKind::Monthly => {
+            let day = self.day;
+            let mut month = today.month();
+            let day_now = today.day() as i32;
+            if day_now <= day {
+                day - day_now
+            } else {
+                let next_date = loop {
+                    let (y, m) = match month {
+                        12 => (today.year() + 1, 1),
+                        m => (today.year(), m + 1),
+                    };
+                    let date = NaiveDate::from_ymd_opt(y, m, day as u32);
+                    if let Some(date) = date {
+                        break date;
+                    } else {
+                        month += 1;
+                    }
+                };
+                let days = next_date.signed_duration_since(today).num_days();
+                days as i32
+            }
+        }
+    };
+    self.days_to_start = days;
+    let next_date = today.checked_add_days(Days::new(days as u64)).unwrap();
+    if next_date > self.end_date.to_naive_date() {
+        None
+    } else {
+        Some(days)
+    }
+}
+}
+
+fn filter_todo(filter: Filter, model: ModelRc<Todo>) -> ModelRc<Todo> {
+    match filter {
+        Filter::All => model,
+        Filter::Once => {
+            let vec = model
+                .iter()
+                .filter(|t| t.kind == TodoKind::Once)
+                .collect::<Vec<Todo>>();
+            Rc::new(slint::VecModel::from(vec)).into()
+        }
+        Filter::Daily => {
+            let vec = model
+                .iter()
+                .filter(|t| t.kind == TodoKind::Daily)
+                .collect::<Vec<Todo>>();
+            Rc::new(slint::VecModel::from(vec)).into()
+        }
+        Filter::Weekly => {
+            let vec = model
+                .iter()
+                .filter(|t| t.kind == TodoKind::Weekly)
+                .collect::<Vec<Todo>>();
+            Rc::new(slint::VecModel::from(vec)).into()
+        }
+        Filter::Monthly => {
+            let vec = model
+                .iter()
+                .filter(|t| t.kind == TodoKind::Monthly)
+                .collect::<Vec<Todo>>();
+            Rc::new(slint::VecModel::from(vec)).into()
+        }
+        Filter::Progress => {
+            let vec = model
+                .iter()
+                .filter(|t| t.kind == TodoKind::Progress)
+                .collect::<Vec<Todo>>();
+            Rc::new(slint::VecModel::from(vec)).into()
+        }
+        Filter::Today => {
+            let vec = model
+                .iter()
+                .filter(|t| t.occurs_on(get_today()))
+                // 今天已经完成的待办不再显示
+                .filter(|t| !t.is_done)
+                .collect::<Vec<Todo>>();
+            Rc::new(slint::VecModel::from(vec)).into()
+        }
+    }
+}
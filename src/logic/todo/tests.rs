@@ -0,0 +1,139 @@
+use crate::{
+    Todo, TodoKind,
+    logic::{SlintDate, record::TodoRecord},
+};
+use chrono::{Days, NaiveDate};
+use std::collections::HashMap;
+
+use super::{get_today, is_valid_todo_id, migrate_todo_ids, next_todo_id, rename_todo_ids};
+
+fn once_todo(date: NaiveDate) -> Todo {
+    let date = SlintDate::from_naive_date(&date);
+    Todo {
+        id: "1".into(),
+        text: "单次".into(),
+        kind: TodoKind::Once,
+        once: date.clone(),
+        start_date: date.clone(),
+        end_date: date,
+        ..Default::default()
+    }
+}
+
+#[test]
+fn once_today_is_not_expired() {
+    let mut todo = once_todo(get_today());
+    assert_eq!(todo.calculate_days_to_start(), Some(0));
+    assert_eq!(todo.days_to_start, 0);
+}
+
+#[test]
+fn once_in_future() {
+    let date = get_today().checked_add_days(Days::new(3)).unwrap();
+    let mut todo = once_todo(date);
+    assert_eq!(todo.calculate_days_to_start(), Some(3));
+}
+
+#[test]
+fn once_in_past_is_expired() {
+    let date = get_today().checked_sub_days(Days::new(1)).unwrap();
+    let mut todo = once_todo(date);
+    assert_eq!(todo.calculate_days_to_start(), None);
+    assert_eq!(todo.days_to_start, -1);
+}
+
+fn record(id: &str, created_at: &str) -> TodoRecord {
+    TodoRecord {
+        id: id.to_string(),
+        created_at: created_at.to_string(),
+        ..Default::default()
+    }
+}
+
+#[test]
+fn next_ids_increase() {
+    let ids = (0..100).map(|_| next_todo_id()).collect::<Vec<_>>();
+    assert!(ids.iter().all(|id| is_valid_todo_id(id)));
+    assert!(ids.windows(2).all(|w| w[0] < w[1]));
+}
+
+#[test]
+fn migrate_legacy_ids() {
+    let mut records = vec![
+        record("1700000000", "2023-11-15"),
+        record("1700000100", "2023-11-15"),
+    ];
+    let migrated_ids = migrate_todo_ids(&mut records);
+    assert!(records.iter().all(|r| is_valid_todo_id(&r.id)));
+    assert_ne!(records[0].id, records[1].id);
+    assert_eq!(migrated_ids.len(), 2);
+    assert_eq!(migrated_ids["1700000000"], records[0].id);
+    assert_eq!(migrated_ids["1700000100"], records[1].id);
+}
+
+#[test]
+fn migrate_keeps_valid_ids() {
+    let id = next_todo_id().to_string();
+    let mut records = vec![record(&id, "2024-01-01")];
+    assert!(migrate_todo_ids(&mut records).is_empty());
+    assert_eq!(records[0].id, id);
+}
+
+#[test]
+fn migrate_duplicate_ids() {
+    // 重复的新格式id保留第一条，后面的换成新id，但不记录对应关系
+    let id = next_todo_id().to_string();
+    let mut records = vec![record(&id, "2024-01-01"), record(&id, "2024-01-02")];
+    let migrated_ids = migrate_todo_ids(&mut records);
+    assert_eq!(records[0].id, id);
+    assert_ne!(records[1].id, id);
+    assert!(is_valid_todo_id(&records[1].id));
+    assert!(migrated_ids.is_empty());
+    // 重复的旧id都换成新id，对应关系指向排在前面的一条
+    let mut records = vec![
+        record("1700000000", "2023-11-16"),
+        record("1700000000", "2023-11-15"),
+    ];
+    let migrated_ids = migrate_todo_ids(&mut records);
+    assert_ne!(records[0].id, records[1].id);
+    assert_eq!(migrated_ids.len(), 1);
+    assert_eq!(migrated_ids["1700000000"], records[1].id);
+}
+
+#[test]
+fn migrate_keeps_creation_order() {
+    let mut records = vec![
+        record("1700000300", "2023-11-17"),
+        record("1700000200", "2023-11-15"),
+        record("1700000100", "2023-11-15"),
+        record("abc", "2023-11-16"),
+    ];
+    migrate_todo_ids(&mut records);
+    // 按创建日期排序，日期相同时按旧id排序
+    assert!(records[2].id < records[1].id);
+    assert!(records[1].id < records[3].id);
+    assert!(records[3].id < records[0].id);
+}
+
+#[test]
+fn migrated_ids_are_newer_than_existing() {
+    let future_id = "00099999999999999999";
+    let mut records = vec![record(future_id, "2024-01-01"), record("1", "2024-01-01")];
+    migrate_todo_ids(&mut records);
+    assert!(records[1].id.as_str() > future_id);
+    assert!(next_todo_id().as_str() > future_id);
+}
+
+#[test]
+fn rename_done_and_progress() {
+    let migrated_ids = HashMap::from([("1700000000".to_string(), "new".to_string())]);
+    let map = HashMap::from([
+        ("1700000000".to_string(), vec![1]),
+        ("kept".to_string(), vec![2]),
+    ]);
+    let map = rename_todo_ids(map, &migrated_ids);
+    assert_eq!(map.len(), 2);
+    assert_eq!(map["new"], vec![1]);
+    assert_eq!(map["kept"], vec![2]);
+    assert!(!map.contains_key("1700000000"));
+}
@@ -7,9 +7,17 @@ fn main() -> Result<(), slint::PlatformError> {
     let weak = app.as_weak();
     logic::set_anime_logic(weak.clone());
     logic::set_todo_logic(weak.clone());
+    logic::set_archive_logic(weak.clone());
+    logic::set_setting_logic(weak.clone());
+    logic::set_home_logic(weak.clone());
+    logic::set_stats_logic(weak.clone());
+    logic::set_news_logic(weak.clone());
+    logic::set_reminder_logic(weak.clone());
     logic::init(weak.clone());
     app.window().on_close_requested(move || {
         slint::CloseRequestResponse::HideWindow // TODO: 完善关闭逻辑
     });
-    app.run()
+    app.run()?;
+    logic::save_reminders();
+    Ok(())
 }